@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+/// A player's stats on every agent, keyed by agent and then by map.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct AgentPool {
+    pub agents: HashMap<String, HashMap<String, AgentTotals>>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct AgentTotals {
+    pub games: i64,
+    pub wins: i64,
+    pub kills: i64,
+    pub deaths: i64,
+    pub score: i64,
+    pub rounds: i64,
+}
+
+/// Agents with fewer games than this are only considered the best if nothing else qualifies
+const MIN_GAMES_FOR_BEST: i64 = 3;
+
+impl AgentTotals {
+    fn add(&mut self, other: &AgentTotals) {
+        self.games += other.games;
+        self.wins += other.wins;
+        self.kills += other.kills;
+        self.deaths += other.deaths;
+        self.score += other.score;
+        self.rounds += other.rounds;
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64 * 100_f64
+    }
+
+    pub fn acs(&self) -> f64 {
+        self.score as f64 / self.rounds.max(1) as f64
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} games, {:.0}% WR, {:.2} KD, {:.0} ACS",
+            self.games,
+            self.win_rate(),
+            self.kills as f64 / self.deaths.max(1) as f64,
+            self.acs()
+        )
+    }
+}
+
+impl AgentPool {
+    pub fn record(&mut self, agent: &str, map: &str, game: AgentTotals) {
+        self.agents
+            .entry(agent.to_string())
+            .or_default()
+            .entry(map.to_string())
+            .or_default()
+            .add(&game);
+    }
+
+    /// Totals per agent, either on a single map (case insensitive) or across all of them.
+    pub fn totals(&self, map: Option<&str>) -> Vec<(&String, AgentTotals)> {
+        let mut totals = self
+            .agents
+            .iter()
+            .map(|(agent, maps)| {
+                let mut total = AgentTotals::default();
+
+                maps.iter()
+                    .filter(|(m, _)| map.is_none_or(|map| m.eq_ignore_ascii_case(map)))
+                    .for_each(|(_, t)| total.add(t));
+
+                (agent, total)
+            })
+            .filter(|(_, t)| t.games > 0)
+            .collect::<Vec<(&String, AgentTotals)>>();
+
+        totals.sort_by(|(aa, a), (ba, b)| b.games.cmp(&a.games).then_with(|| aa.cmp(ba)));
+        totals
+    }
+
+    /// Picks the agent with the best win rate, then ACS, preferring ones with enough games to mean something.
+    pub fn best(&self, map: Option<&str>) -> Option<(&String, AgentTotals)> {
+        let totals = self.totals(map);
+        let enough_games = totals.iter().any(|(_, t)| t.games >= MIN_GAMES_FOR_BEST);
+
+        totals
+            .into_iter()
+            .filter(|(_, t)| !enough_games || t.games >= MIN_GAMES_FOR_BEST)
+            .max_by(|(_, a), (_, b)| {
+                a.win_rate()
+                    .total_cmp(&b.win_rate())
+                    .then_with(|| a.acs().total_cmp(&b.acs()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn games(games: i64, wins: i64, score: i64) -> AgentTotals {
+        AgentTotals {
+            games,
+            wins,
+            kills: games * 15,
+            deaths: games * 15,
+            score,
+            rounds: games * 20,
+        }
+    }
+
+    fn pool() -> AgentPool {
+        let mut pool = AgentPool::default();
+        pool.record("Sage", "Ascent", games(3, 1, 9000));
+        pool.record("Sage", "Bind", games(2, 2, 6000));
+        pool.record("Jett", "Ascent", games(3, 2, 12000));
+        // Unbeaten, but only once
+        pool.record("Reyna", "Bind", games(1, 1, 8000));
+        pool
+    }
+
+    #[test]
+    fn totals_add_up_maps_and_filter_by_map() {
+        let pool = pool();
+
+        let summarize = |map| {
+            pool.totals(map)
+                .into_iter()
+                .map(|(agent, t)| (agent.as_str(), t.games, t.wins))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            summarize(None),
+            [("Sage", 5, 3), ("Jett", 3, 2), ("Reyna", 1, 1)]
+        );
+        // Case insensitive, and agents never played there are left out
+        assert_eq!(summarize(Some("ascent")), [("Jett", 3, 2), ("Sage", 3, 1)]);
+        assert!(summarize(Some("Haven")).is_empty());
+    }
+
+    #[test]
+    fn best_needs_enough_games_unless_nothing_has_them() {
+        let pool = pool();
+        let best = |map| pool.best(map).map(|(agent, _)| agent.as_str());
+
+        // Reyna's 100% is a single game, Jett's 67% beats Sage's 60%
+        assert_eq!(best(None), Some("Jett"));
+        // Nothing has 3 games on Bind, so Reyna's single win and Sage's two are both fair game, ACS breaks the tie
+        assert_eq!(best(Some("Bind")), Some("Reyna"));
+        assert_eq!(best(Some("Haven")), None);
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    future::pending,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Result};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use tokio::task;
+use tracing::warn;
+
+use crate::{
+    cache::{Fetched, ResponseCache},
+    config::Config,
+    metrics::METRICS,
+    replay::{Recorder, Replay},
+    shutdown::Shutdown,
+    HendrixMatchesResponse, HendrixMmrResponse, MatchDatum, MmrDatum, PlayerData,
+};
+
+pub const MATCH_URL: &str = "/valorant/v3/matches";
+pub const MMR_HISTORY_URL: &str = "/valorant/v1/mmr-history";
+
+/// HenrikDev API client, cheap to clone.
+#[derive(Clone)]
+pub struct Api {
+    client: Client,
+    base_url: String,
+    region: String,
+    queue: String,
+    /// Which tracker is asking, recordings are kept apart per tracker
+    tracker: &'static str,
+    recorder: Option<Arc<Recorder>>,
+    /// Shared by every client so they don't fetch the same thing twice
+    cache: Arc<ResponseCache>,
+    /// Responses come from here instead of HenrikDev when set
+    replay: Option<Arc<Replay>>,
+    /// When the latest replayed response was received, the replay's clock
+    replayed_at: Arc<AtomicI64>,
+}
+
+impl Api {
+    fn region<'a>(&'a self, player: &'a PlayerData) -> &'a str {
+        player.region.as_deref().unwrap_or(&self.region)
+    }
+
+    pub fn new(config: &Config) -> Result<Self> {
+        Ok(Self {
+            client: Client::new(),
+            base_url: config.base_url.clone(),
+            region: config.region.clone(),
+            queue: config.queue.clone(),
+            tracker: "",
+            recorder: match &config.record_dir {
+                Some(dir) => Some(Arc::new(Recorder::new(dir)?)),
+                None => None,
+            },
+            cache: Arc::new(ResponseCache::new(config.cache_ttl())),
+            replay: match &config.replay_dir {
+                Some(dir) => Some(Arc::new(Replay::load(dir)?)),
+                None => None,
+            },
+            replayed_at: Arc::new(AtomicI64::new(0)),
+        })
+    }
+
+    /// A client for one tracker, so a replay gives it back the same responses in the same order.
+    pub fn for_tracker(&self, tracker: &'static str) -> Self {
+        Self {
+            tracker,
+            replayed_at: Arc::new(AtomicI64::new(0)),
+            ..self.clone()
+        }
+    }
+
+    /// Unix seconds, going by the recordings when replaying.
+    pub fn now(&self) -> i64 {
+        match &self.replay {
+            Some(_) => self.replayed_at.load(Ordering::Relaxed),
+            None => unix_now(),
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Makes the next MMR lookup for `player` fetch it again, for when it's known to have changed.
+    pub fn forget_mmr(&self, player: &PlayerData) {
+        self.cache.forget(&self.mmr_url(player));
+    }
+
+    /// Waits `duration` between polls, returns whether a shutdown was triggered.
+    pub async fn wait(&self, shutdown: &mut Shutdown, duration: Duration) -> bool {
+        match &self.replay {
+            // Time only moves with the recordings, so keep going until they run out
+            Some(replay) if !replay.is_done(self.tracker) => {
+                task::yield_now().await;
+                shutdown.is_triggered()
+            }
+            Some(_) => {
+                shutdown.triggered().await;
+                true
+            }
+            None => shutdown.sleep(duration).await,
+        }
+    }
+
+    /// Waits until every recording has been replayed, never returns when not replaying.
+    pub async fn replay_finished(&self) {
+        match &self.replay {
+            Some(replay) => replay.finished().await,
+            None => pending().await,
+        }
+    }
+
+    /// Decodes the next response from `endpoint` for `player`, fetched from `url` unless replaying.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        player: &PlayerData,
+        url: String,
+    ) -> Result<T> {
+        let body = match &self.replay {
+            Some(replay) => {
+                let recorded = replay.next(self.tracker, endpoint, player)?;
+                self.replayed_at
+                    .fetch_max(recorded.received_at, Ordering::Relaxed);
+                Arc::new(recorded.body)
+            }
+            None => {
+                let body = self
+                    .cache
+                    .get(endpoint, &url, || self.fetch(endpoint, &url))
+                    .await?;
+
+                // Every tracker records what it got, even from the cache, so each can be replayed on its own
+                if let Some(recorder) = &self.recorder {
+                    let recorded = recorder
+                        .record(self.tracker, endpoint, player, unix_now(), &body)
+                        .await;
+                    if let Err(e) = recorded {
+                        warn!("Failed to record response -> {e:#}");
+                    }
+                }
+
+                body
+            }
+        };
+
+        serde_json::from_slice(&body).map_err(|e| {
+            METRICS
+                .api_decode_failures
+                .with_label_values(&[endpoint])
+                .inc();
+            e.into()
+        })
+    }
+
+    /// Fetches `url`, recording the latency and status under `endpoint`.
+    async fn fetch(&self, endpoint: &'static str, url: &str) -> Result<Fetched> {
+        let started = Instant::now();
+        let response = self.client.get(url).send().await;
+
+        METRICS
+            .api_latency
+            .with_label_values(&[endpoint])
+            .observe(started.elapsed().as_secs_f64());
+
+        let status = match &response {
+            Ok(r) => r.status().as_u16().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        METRICS
+            .api_responses
+            .with_label_values(&[endpoint, &status])
+            .inc();
+
+        let response = response?;
+        let cacheable = response.status().is_success();
+        let body = response.bytes().await?.to_vec();
+
+        Ok(Fetched { body, cacheable })
+    }
+
+    pub async fn lookup_player_matches(&self, player: &PlayerData) -> Result<MatchDatum> {
+        let Self {
+            base_url, queue, ..
+        } = self;
+        let PlayerData { name, tag, .. } = player;
+        let region = self.region(player);
+
+        let response = self
+            .get::<HendrixMatchesResponse>(
+                "matches",
+                player,
+                format!("{base_url}{MATCH_URL}/{region}/{name}/{tag}?filter={queue}&size=1"),
+            )
+            .await?;
+
+        if response.status != 200 {
+            bail!(
+                "got status of {} instead of 200 -> {:?}",
+                response.status,
+                response
+            );
+        }
+
+        match response.data {
+            Some(mut d) if !d.is_empty() => Ok(d.remove(0)),
+            _ => bail!("no matches found"),
+        }
+    }
+
+    fn mmr_url(&self, player: &PlayerData) -> String {
+        let base_url = &self.base_url;
+        let PlayerData { name, tag, .. } = player;
+        let region = self.region(player);
+
+        format!("{base_url}{MMR_HISTORY_URL}/{region}/{name}/{tag}?size=1")
+    }
+
+    pub async fn lookup_player_mmr(&self, player: &PlayerData) -> Result<MmrDatum> {
+        let response = self
+            .get::<HendrixMmrResponse>("mmr_history", player, self.mmr_url(player))
+            .await?;
+
+        if response.status != 200 {
+            bail!(
+                "got status of {} instead of 200 -> {:?}",
+                response.status,
+                response
+            );
+        }
+
+        match response.data {
+            Some(mut d) if !d.is_empty() => Ok(d.remove(0)),
+            _ => bail!("no mmr found"),
+        }
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
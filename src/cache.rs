@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use tokio::{sync::OnceCell, time::Instant};
+
+use crate::metrics::METRICS;
+
+/// A response body, and whether it's worth keeping for the next request.
+pub struct Fetched {
+    pub body: Vec<u8>,
+    pub cacheable: bool,
+}
+
+/// Successful responses kept for a while, so the same URL is only fetched once per poll.
+///
+/// Requests for a URL that's already being fetched wait for that one instead of sending their own.
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+/// Why a fetch didn't end up in the cache.
+enum Uncached {
+    Body(Vec<u8>),
+    Failed(anyhow::Error),
+}
+
+struct Entry {
+    started: Instant,
+    body: Arc<OnceCell<Arc<Vec<u8>>>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get<F, Fut>(&self, endpoint: &str, url: &str, fetch: F) -> Result<Arc<Vec<u8>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Fetched>>,
+    {
+        if self.ttl.is_zero() {
+            return Ok(Arc::new(fetch().await?.body));
+        }
+
+        let cell = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|_, e| e.started.elapsed() < self.ttl);
+
+            entries
+                .entry(url.to_string())
+                .or_insert_with(|| Entry {
+                    started: Instant::now(),
+                    body: Arc::new(OnceCell::new()),
+                })
+                .body
+                .clone()
+        };
+
+        if let Some(body) = cell.get() {
+            METRICS.api_cache_hits.with_label_values(&[endpoint]).inc();
+            return Ok(body.clone());
+        }
+
+        let mut fetched = false;
+        let fetched_here = &mut fetched;
+
+        let cached = cell
+            .get_or_try_init(|| async move {
+                *fetched_here = true;
+
+                match fetch().await {
+                    Ok(Fetched {
+                        body,
+                        cacheable: true,
+                    }) => Ok(Arc::new(body)),
+                    Ok(Fetched { body, .. }) => Err(Uncached::Body(body)),
+                    Err(e) => Err(Uncached::Failed(e)),
+                }
+            })
+            .await;
+
+        // Someone else's fetch finished while waiting for it
+        if !fetched {
+            METRICS.api_cache_hits.with_label_values(&[endpoint]).inc();
+        }
+
+        match cached {
+            Ok(body) => Ok(body.clone()),
+            Err(Uncached::Body(body)) => Ok(Arc::new(body)),
+            Err(Uncached::Failed(e)) => Err(e),
+        }
+    }
+
+    /// Drops a URL so the next request fetches it again.
+    pub fn forget(&self, url: &str) {
+        self.entries.lock().unwrap().remove(url);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::bail;
+    use tokio::time::sleep;
+
+    use super::*;
+
+    const URL: &str = "http://henrik/valorant/v1/mmr-history/na/Zero/SHOT?size=1";
+
+    /// Counts how many times it's called, answering after a second.
+    async fn fetch(calls: &AtomicUsize, cacheable: bool) -> Result<Fetched> {
+        let call = calls.fetch_add(1, Ordering::SeqCst);
+        sleep(Duration::from_secs(1)).await;
+
+        Ok(Fetched {
+            body: format!("response {call}").into_bytes(),
+            cacheable,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_requests_share_one_fetch() {
+        let cache = ResponseCache::new(Duration::from_secs(30));
+        let calls = AtomicUsize::new(0);
+
+        let (a, b) = tokio::join!(
+            cache.get("mmr_history", URL, || fetch(&calls, true)),
+            cache.get("mmr_history", URL, || fetch(&calls, true)),
+        );
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(a.unwrap(), b.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refetches_after_the_ttl_or_when_forgotten() {
+        let cache = ResponseCache::new(Duration::from_secs(30));
+        let calls = AtomicUsize::new(0);
+
+        cache
+            .get("mmr_history", URL, || fetch(&calls, true))
+            .await
+            .unwrap();
+        cache
+            .get("mmr_history", URL, || fetch(&calls, true))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        sleep(Duration::from_secs(30)).await;
+        cache
+            .get("mmr_history", URL, || fetch(&calls, true))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        cache.forget(URL);
+        cache
+            .get("mmr_history", URL, || fetch(&calls, true))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn never_keeps_failures() {
+        let cache = ResponseCache::new(Duration::from_secs(30));
+        let calls = AtomicUsize::new(0);
+
+        let rate_limited = cache.get("mmr_history", URL, || fetch(&calls, false)).await;
+        assert_eq!(*rate_limited.unwrap(), b"response 0".to_vec());
+
+        let failed = cache
+            .get("mmr_history", URL, || async { bail!("connection refused") })
+            .await;
+        assert!(failed.is_err());
+
+        cache
+            .get("mmr_history", URL, || fetch(&calls, true))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,403 @@
+use serde_json::Value;
+use serenity::{
+    async_trait,
+    builder::CreateEmbed,
+    client::bridge::gateway::event::ShardStageUpdateEvent,
+    gateway::ConnectionStage,
+    model::{
+        application::{
+            command::{Command, CommandOptionType},
+            interaction::{
+                application_command::ApplicationCommandInteraction, Interaction,
+                InteractionResponseType,
+            },
+        },
+        gateway::Ready,
+        Permissions,
+    },
+    prelude::{Context, EventHandler},
+};
+use tracing::{error, info};
+
+use crate::{
+    guilds::{GuildConfig, SharedGuilds},
+    health::HEALTH,
+    party::party_size_name,
+    records::{match_url, Record, RecordKind},
+    store::SharedStore,
+    PlayerData,
+};
+
+pub struct Handler {
+    pub store: SharedStore,
+    pub guilds: SharedGuilds,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        info!(user = %ready.user.name, "Connected to Discord");
+        HEALTH.set_gateway_connected(true);
+
+        let commands = Command::set_global_application_commands(&ctx.http, |c| {
+            c.create_application_command(|c| {
+                c.name("synergy")
+                    .description("Show how a player performs solo and with each party")
+                    .create_option(|o| player_option(o))
+            })
+            .create_application_command(|c| {
+                c.name("agents")
+                    .description(
+                        "Show a player's agent stats and their best agent, optionally on one map",
+                    )
+                    .create_option(|o| player_option(o))
+                    .create_option(|o| {
+                        o.name("map")
+                            .description("Map name, ex. Ascent")
+                            .kind(CommandOptionType::String)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|c| {
+                c.name("records")
+                    .description("Show a player's personal bests")
+                    .create_option(|o| player_option(o))
+            })
+            .create_application_command(|c| {
+                c.name("track")
+                    .description("Start tracking a player in this server")
+                    .default_member_permissions(Permissions::MANAGE_GUILD)
+                    .dm_permission(false)
+                    .create_option(|o| player_option(o))
+            })
+            .create_application_command(|c| {
+                c.name("untrack")
+                    .description("Stop tracking a player in this server")
+                    .default_member_permissions(Permissions::MANAGE_GUILD)
+                    .dm_permission(false)
+                    .create_option(|o| player_option(o))
+            })
+            .create_application_command(|c| {
+                c.name("channel")
+                    .description("Post a feed to this channel, or stop posting it")
+                    .default_member_permissions(Permissions::MANAGE_GUILD)
+                    .dm_permission(false)
+                    .create_option(|o| {
+                        o.name("feed")
+                            .description("Which feed to post here")
+                            .kind(CommandOptionType::String)
+                            .add_string_choice("Game log", "game")
+                            .add_string_choice("MMR leaderboard", "mmr")
+                            .required(true)
+                    })
+                    .create_option(|o| {
+                        o.name("enabled")
+                            .description("False to stop posting the feed, defaults to true")
+                            .kind(CommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|c| {
+                c.name("streaks")
+                    .description("Set how long streaks get before they're announced in this server")
+                    .default_member_permissions(Permissions::MANAGE_GUILD)
+                    .dm_permission(false)
+                    .create_option(|o| {
+                        o.name("win")
+                            .description("Wins in a row, 0 to disable")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("loss")
+                            .description("Losses in a row, 0 to disable")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .required(false)
+                    })
+            })
+        })
+        .await;
+
+        if let Err(e) = commands {
+            error!("Failed to register slash commands -> {e}");
+        }
+    }
+
+    async fn shard_stage_update(&self, _ctx: Context, event: ShardStageUpdateEvent) {
+        HEALTH.set_gateway_connected(event.new == ConnectionStage::Connected);
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::ApplicationCommand(command) = interaction else {
+            return;
+        };
+
+        let embed = match command.data.name.as_str() {
+            "synergy" => self.synergy(&command).await,
+            "agents" => self.agents(&command).await,
+            "records" => self.records(&command).await,
+            "track" | "untrack" | "channel" | "streaks" => self.configure(&command).await,
+            _ => return,
+        };
+
+        let response = command
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| d.set_embed(embed))
+            })
+            .await;
+
+        if let Err(e) = response {
+            error!(command = %command.data.name, "Failed to respond to command -> {e}");
+        }
+    }
+}
+
+impl Handler {
+    async fn synergy(&self, command: &ApplicationCommandInteraction) -> CreateEmbed {
+        let name = string_option(command, "player");
+        let store = self.store.lock().await;
+
+        let mut embed = CreateEmbed::default();
+
+        let Some(stats) = store.find_puuid(&name).and_then(|p| store.party.get(p)) else {
+            return not_found(embed, &name);
+        };
+
+        embed.title(format!("{name}'s Party Synergy"));
+
+        let mut by_size = stats.by_size.iter().collect::<Vec<_>>();
+        by_size.sort_by_key(|(size, _)| **size);
+
+        for (size, totals) in by_size {
+            embed.field(party_size_name(*size), totals.summary(), false);
+        }
+
+        let mut by_teammates = stats
+            .by_teammates
+            .iter()
+            .filter(|(teammates, _)| !teammates.is_empty())
+            .collect::<Vec<_>>();
+        by_teammates.sort_by_key(|(_, totals)| -totals.games);
+
+        for (teammates, totals) in by_teammates {
+            embed.field(format!("With {teammates}"), totals.summary(), false);
+        }
+
+        embed
+    }
+
+    async fn agents(&self, command: &ApplicationCommandInteraction) -> CreateEmbed {
+        let name = string_option(command, "player");
+        let map = Some(string_option(command, "map")).filter(|m| !m.is_empty());
+        let store = self.store.lock().await;
+
+        let mut embed = CreateEmbed::default();
+
+        let Some(pool) = store.find_puuid(&name).and_then(|p| store.agents.get(p)) else {
+            return not_found(embed, &name);
+        };
+
+        let totals = pool.totals(map.as_deref());
+        if totals.is_empty() {
+            return not_found(embed, &format!("{name} on {}", map.unwrap_or_default()));
+        }
+
+        match &map {
+            Some(map) => embed.title(format!("{name}'s Agents on {map}")),
+            None => embed.title(format!("{name}'s Agents")),
+        };
+
+        if let Some((agent, best)) = pool.best(map.as_deref()) {
+            embed.description(format!("Best agent: **{agent}** ({})", best.summary()));
+        }
+
+        for (agent, t) in totals {
+            embed.field(agent, t.summary(), false);
+        }
+
+        embed
+    }
+
+    async fn records(&self, command: &ApplicationCommandInteraction) -> CreateEmbed {
+        let name = string_option(command, "player");
+        let store = self.store.lock().await;
+
+        let mut embed = CreateEmbed::default();
+
+        let Some(records) = store.find_puuid(&name).and_then(|p| store.records.get(p)) else {
+            return not_found(embed, &name);
+        };
+
+        embed.title(format!("{name}'s Personal Bests"));
+
+        if let Some(c) = store
+            .find_puuid(&name)
+            .and_then(|p| store.round_contributions.get(p))
+        {
+            embed.description(format!(
+                "All time KAST of {:.0}% over {} rounds, with {} trade kills and {} traded deaths",
+                c.kast(),
+                c.rounds,
+                c.trade_kills,
+                c.traded_deaths
+            ));
+        }
+
+        for (kind, record) in records.entries() {
+            embed.field(kind.label(), record_summary(kind, record), false);
+        }
+
+        embed
+    }
+
+    /// Handles every command that changes this guild's config, saving it afterwards.
+    async fn configure(&self, command: &ApplicationCommandInteraction) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+
+        let Some(guild_id) = command.guild_id else {
+            embed.description("This command only works in a server.");
+            return embed;
+        };
+
+        let mut guilds = self.guilds.write().await;
+        let guild = guilds.guilds.entry(guild_id.0).or_default();
+
+        let description = match command.data.name.as_str() {
+            "track" => track(guild, &string_option(command, "player"), true),
+            "untrack" => track(guild, &string_option(command, "player"), false),
+            "channel" => {
+                let channel = Some(command.channel_id.0).filter(|_| {
+                    option(command, "enabled")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true)
+                });
+
+                let feed = string_option(command, "feed");
+                if feed == "game" {
+                    guild.game_channel = channel;
+                } else {
+                    guild.mmr_channel = channel;
+                }
+
+                match channel {
+                    Some(c) => format!("Posting the {feed} feed to <#{c}>."),
+                    None => format!("Stopped posting the {feed} feed."),
+                }
+            }
+            _ => {
+                if let Some(win) = option(command, "win").and_then(|v| v.as_i64()) {
+                    guild.settings.win_streak = Some(win);
+                }
+
+                if let Some(loss) = option(command, "loss").and_then(|v| v.as_i64()) {
+                    guild.settings.loss_streak = Some(loss);
+                }
+
+                format!(
+                    "Announcing win streaks every {} and loss streaks every {} games.",
+                    display_setting(guild.settings.win_streak),
+                    display_setting(guild.settings.loss_streak)
+                )
+            }
+        };
+
+        guilds.save().await;
+
+        embed.description(description);
+        embed
+    }
+}
+
+fn track(guild: &mut GuildConfig, tag: &str, add: bool) -> String {
+    let Some(player) = PlayerData::parse(tag) else {
+        return format!("Invalid player tag '{tag}', it should look like Name#Tag.");
+    };
+
+    let tracked = guild.players.contains(&player);
+
+    match (add, tracked) {
+        (true, false) => {
+            guild.players.push(player.clone());
+            format!("Now tracking {player}.")
+        }
+        (false, true) => {
+            guild.players.retain(|p| p != &player);
+            format!("Stopped tracking {player}.")
+        }
+        (true, true) => format!("{player} is already tracked."),
+        (false, false) => format!("{player} isn't tracked."),
+    }
+}
+
+fn display_setting(value: Option<i64>) -> String {
+    value.map_or("(default)".to_string(), |v| v.to_string())
+}
+
+fn record_summary(kind: RecordKind, record: &Record) -> String {
+    format!(
+        "**{}** as {} on {}, <t:{}:d> ([match]({}))",
+        kind.format(record.value),
+        record.agent,
+        record.map,
+        record.date,
+        match_url(&record.match_id)
+    )
+}
+
+fn player_option(
+    o: &mut serenity::builder::CreateApplicationCommandOption,
+) -> &mut serenity::builder::CreateApplicationCommandOption {
+    o.name("player")
+        .description("Player tag, ex. Cooper#NA123")
+        .kind(CommandOptionType::String)
+        .required(true)
+}
+
+fn option<'a>(command: &'a ApplicationCommandInteraction, name: &str) -> Option<&'a Value> {
+    command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.value.as_ref())
+}
+
+fn string_option(command: &ApplicationCommandInteraction, name: &str) -> String {
+    option(command, name)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn not_found(mut embed: CreateEmbed, name: &str) -> CreateEmbed {
+    embed
+        .title("No data")
+        .description(format!("No tracked games found for {name}."));
+    embed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracking_ignores_case() {
+        let mut guild = GuildConfig::default();
+
+        // (command, reply)
+        let commands = [
+            (("Cooper#NA123", true), "Now tracking Cooper#NA123."),
+            (("cooper#na123", true), "cooper#na123 is already tracked."),
+            (("COOPER#Na123", false), "Stopped tracking COOPER#Na123."),
+            (("Cooper#NA123", false), "Cooper#NA123 isn't tracked."),
+        ];
+
+        for ((tag, add), reply) in commands {
+            assert_eq!(track(&mut guild, tag, add), reply);
+        }
+        assert!(guild.players.is_empty());
+    }
+}
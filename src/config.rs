@@ -0,0 +1,740 @@
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use serde_derive::Deserialize;
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    guilds::{GuildConfig, GuildSettings},
+    output::{Feed, FeedOutput, Output},
+    streak::StreakThresholds,
+    PlayerData,
+};
+
+const DEFAULT_CONFIG_FILE: &str = "./config.toml";
+
+pub const REGIONS: [&str; 6] = ["na", "eu", "ap", "kr", "latam", "br"];
+const LOG_FORMATS: [&str; 2] = ["pretty", "json"];
+const QUEUES: [&str; 9] = [
+    "competitive",
+    "unrated",
+    "swiftplay",
+    "spikerush",
+    "deathmatch",
+    "escalation",
+    "replication",
+    "premier",
+    "custom",
+];
+
+/// Every setting can be given as a flag, an environment variable, or in the config file, in that order of priority.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// Path to the TOML config file
+    #[arg(short, long, env = "VALORANT_TRACKER_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Discord Bot Token, prefer --token-file or the DISCORD_TOKEN env variable so it doesn't show up in `ps`
+    #[arg(short, long)]
+    token: Option<String>,
+
+    /// File containing the Discord Bot Token, or set DISCORD_TOKEN_FILE
+    #[arg(long)]
+    token_file: Option<PathBuf>,
+
+    // Read separately from the flags, so either flag beats either variable
+    #[arg(skip = std::env::var("DISCORD_TOKEN").ok())]
+    env_token: Option<String>,
+
+    #[arg(skip = std::env::var_os("DISCORD_TOKEN_FILE").map(PathBuf::from))]
+    env_token_file: Option<PathBuf>,
+
+    /// Game Log Channel ID
+    #[arg(short, long, env = "GAME_CHANNEL")]
+    game_channel: Option<u64>,
+
+    /// MMR Status Channel ID
+    #[arg(short, long, env = "MMR_CHANNEL")]
+    mmr_channel: Option<u64>,
+
+    /// Discord webhook URL to post the game log to, works without a bot token
+    #[arg(long, env = "GAME_WEBHOOK", hide_env_values = true)]
+    game_webhook: Option<String>,
+
+    /// Discord webhook URL to post the MMR leaderboard to, works without a bot token
+    #[arg(long, env = "MMR_WEBHOOK", hide_env_values = true)]
+    mmr_webhook: Option<String>,
+
+    /// Channel ID to report tracker crashes to
+    #[arg(long, env = "ADMIN_CHANNEL")]
+    admin_channel: Option<u64>,
+
+    /// File with a player tag (Name#Tag) on each line
+    #[arg(long, env = "PLAYER_FILE")]
+    player_file: Option<PathBuf>,
+
+    /// Seconds between polls of a player who's still in a session
+    #[arg(long, env = "MIN_POLL_INTERVAL")]
+    min_poll_interval: Option<u64>,
+
+    /// Seconds between polls of a player who hasn't been seen playing yet, or only stopped a little while ago
+    #[arg(long, env = "POLL_INTERVAL")]
+    poll_interval: Option<u64>,
+
+    /// Seconds between polls of a player who hasn't played in a long time
+    #[arg(long, env = "MAX_POLL_INTERVAL")]
+    max_poll_interval: Option<u64>,
+
+    /// Region the players are in, one of na, eu, ap, kr, latam or br
+    #[arg(long, env = "REGION")]
+    region: Option<String>,
+
+    /// Only games in this queue are posted, ex. competitive or unrated
+    #[arg(long, env = "QUEUE")]
+    queue: Option<String>,
+
+    /// Base URL of the HenrikDev API
+    #[arg(long, env = "BASE_URL")]
+    base_url: Option<String>,
+
+    /// Minutes without a new game before a play session is considered over and recapped
+    #[arg(long, env = "SESSION_GAP")]
+    session_gap: Option<u64>,
+
+    /// Announce win streaks every this many wins in a row, 0 to disable
+    #[arg(long, env = "WIN_STREAK")]
+    win_streak: Option<i64>,
+
+    /// Announce loss streaks every this many losses in a row, 0 to disable
+    #[arg(long, env = "LOSS_STREAK")]
+    loss_streak: Option<i64>,
+
+    /// Seconds a teammate has to avenge a death for it to count as traded
+    #[arg(long, env = "TRADE_WINDOW")]
+    trade_window: Option<u64>,
+
+    /// Which logs to show, ex. info or valorant_tracker=debug,serenity=warn
+    #[arg(long, env = "LOG_FILTER")]
+    log_filter: Option<String>,
+
+    /// Log output, pretty for the console or json for one JSON object per line
+    #[arg(long, env = "LOG_FORMAT")]
+    log_format: Option<String>,
+
+    /// Address to serve /metrics, /healthz and /readyz on, ex. 127.0.0.1:9100, off if not set
+    #[arg(long, env = "STATUS_ADDRESS")]
+    status_address: Option<SocketAddr>,
+
+    /// Seconds without a successful poll before a tracker is reported unhealthy, twice the max poll interval (at least 600) by default
+    #[arg(long, env = "STALE_AFTER")]
+    stale_after: Option<u64>,
+
+    /// Seconds HenrikDev responses are reused for, half the min poll interval by default, 0 to disable
+    #[arg(long, env = "CACHE_TTL")]
+    cache_ttl: Option<u64>,
+
+    /// Print every post to the terminal instead of sending it, and leave the saved state alone
+    #[arg(long, env = "DRY_RUN")]
+    dry_run: bool,
+
+    /// Save every raw HenrikDev response to this directory, for --replay-dir
+    #[arg(long, env = "RECORD_DIR")]
+    record_dir: Option<PathBuf>,
+
+    /// Play back responses saved with --record-dir instead of calling HenrikDev, without touching the saved state
+    #[arg(long, env = "REPLAY_DIR")]
+    replay_dir: Option<PathBuf>,
+}
+
+/// The config file, anything missing falls back to the defaults.
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    token: Option<String>,
+    token_file: Option<PathBuf>,
+    game_channel: Option<u64>,
+    mmr_channel: Option<u64>,
+    game_webhook: Option<String>,
+    mmr_webhook: Option<String>,
+    admin_channel: Option<u64>,
+    player_file: Option<PathBuf>,
+    min_poll_interval: Option<u64>,
+    poll_interval: Option<u64>,
+    max_poll_interval: Option<u64>,
+    region: Option<String>,
+    queue: Option<String>,
+    base_url: Option<String>,
+    session_gap: Option<u64>,
+    win_streak: Option<i64>,
+    loss_streak: Option<i64>,
+    trade_window: Option<u64>,
+    log_filter: Option<String>,
+    log_format: Option<String>,
+    status_address: Option<SocketAddr>,
+    stale_after: Option<u64>,
+    cache_ttl: Option<u64>,
+    dry_run: Option<bool>,
+    record_dir: Option<PathBuf>,
+    replay_dir: Option<PathBuf>,
+    #[serde(default)]
+    outputs: Vec<FeedOutput>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Not needed when only posting to webhooks
+    pub token: Option<String>,
+    pub game_channel: Option<u64>,
+    pub mmr_channel: Option<u64>,
+    pub game_webhook: Option<String>,
+    pub mmr_webhook: Option<String>,
+    /// Extra outputs, only settable in the config file
+    pub outputs: Vec<FeedOutput>,
+    pub admin_channel: Option<u64>,
+    pub player_file: PathBuf,
+    /// Seconds
+    pub min_poll_interval: u64,
+    /// Seconds
+    pub poll_interval: u64,
+    /// Seconds
+    pub max_poll_interval: u64,
+    pub region: String,
+    pub queue: String,
+    pub base_url: String,
+    /// Minutes
+    pub session_gap: u64,
+    pub win_streak: i64,
+    pub loss_streak: i64,
+    /// Seconds
+    pub trade_window: u64,
+    pub log_filter: String,
+    pub log_format: String,
+    pub status_address: Option<SocketAddr>,
+    /// Seconds
+    pub stale_after: u64,
+    /// Seconds
+    pub cache_ttl: u64,
+    pub dry_run: bool,
+    pub record_dir: Option<PathBuf>,
+    pub replay_dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        Self::from_args(Args::parse())
+    }
+
+    pub fn from_args(args: Args) -> Result<Self> {
+        let file = match &args.config {
+            Some(path) => read_file(path)?,
+            None if PathBuf::from(DEFAULT_CONFIG_FILE).exists() => {
+                read_file(&PathBuf::from(DEFAULT_CONFIG_FILE))?
+            }
+            None => FileConfig::default(),
+        };
+
+        // A token beats a token file from the same place, and flags beat the environment, which beats the file
+        let token = [
+            (args.token, args.token_file),
+            (args.env_token, args.env_token_file),
+            (file.token, file.token_file),
+        ]
+        .into_iter()
+        .find(|(token, path)| token.is_some() || path.is_some());
+
+        let token = match token {
+            Some((Some(token), _)) => token,
+            Some((None, Some(path))) => read_token(&path)?,
+            _ => String::new(),
+        };
+
+        let dry_run = args.dry_run || file.dry_run.unwrap_or_default();
+        let poll_interval = args.poll_interval.or(file.poll_interval).unwrap_or(60);
+        let min_poll_interval = args
+            .min_poll_interval
+            .or(file.min_poll_interval)
+            .unwrap_or(poll_interval.min(30));
+        let max_poll_interval = args
+            .max_poll_interval
+            .or(file.max_poll_interval)
+            .unwrap_or(15 * 60);
+
+        let config = Self {
+            token: Some(token.trim().to_string()).filter(|t| !t.is_empty()),
+            game_channel: args.game_channel.or(file.game_channel),
+            mmr_channel: args.mmr_channel.or(file.mmr_channel),
+            game_webhook: args.game_webhook.or(file.game_webhook),
+            mmr_webhook: args.mmr_webhook.or(file.mmr_webhook),
+            outputs: file.outputs,
+            // Crash reports would go to Discord too
+            admin_channel: args
+                .admin_channel
+                .or(file.admin_channel)
+                .filter(|_| !dry_run),
+            player_file: args
+                .player_file
+                .or(file.player_file)
+                .unwrap_or_else(|| PathBuf::from("./players.txt")),
+            min_poll_interval,
+            poll_interval,
+            max_poll_interval,
+            region: args
+                .region
+                .or(file.region)
+                .unwrap_or_else(|| "na".to_string())
+                .to_lowercase(),
+            queue: args
+                .queue
+                .or(file.queue)
+                .unwrap_or_else(|| "competitive".to_string())
+                .to_lowercase(),
+            base_url: args
+                .base_url
+                .or(file.base_url)
+                .unwrap_or_else(|| "https://api.henrikdev.xyz".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            session_gap: args.session_gap.or(file.session_gap).unwrap_or(30),
+            win_streak: args.win_streak.or(file.win_streak).unwrap_or(5),
+            loss_streak: args.loss_streak.or(file.loss_streak).unwrap_or(4),
+            trade_window: args.trade_window.or(file.trade_window).unwrap_or(5),
+            log_filter: args
+                .log_filter
+                .or(file.log_filter)
+                .unwrap_or_else(|| "info".to_string()),
+            log_format: args
+                .log_format
+                .or(file.log_format)
+                .unwrap_or_else(|| "pretty".to_string())
+                .to_lowercase(),
+            status_address: args.status_address.or(file.status_address),
+            // Long enough for a poll of the idlest player to come around
+            stale_after: args
+                .stale_after
+                .or(file.stale_after)
+                .unwrap_or((2 * max_poll_interval).max(600)),
+            cache_ttl: args
+                .cache_ttl
+                .or(file.cache_ttl)
+                .unwrap_or(min_poll_interval / 2),
+            dry_run,
+            record_dir: args.record_dir.or(file.record_dir),
+            replay_dir: args.replay_dir.or(file.replay_dir),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let outputs = self.default_guild(vec![]);
+        let outputs = [outputs.game_outputs(), outputs.mmr_outputs()].concat();
+
+        for output in &outputs {
+            output.validate()?;
+        }
+
+        // Nothing gets sent in a dry run, so there's no need for a token
+        if self.token.is_none() && !self.dry_run {
+            if outputs.is_empty() {
+                bail!(
+                    "no Discord token given, set one with --token-file, DISCORD_TOKEN, or token_file in the config file, or only post to webhooks and other outputs"
+                );
+            }
+
+            if outputs.iter().any(|o| matches!(o, Output::Channel { .. }))
+                || self.admin_channel.is_some()
+            {
+                bail!("posting to channels needs a Discord token, use webhooks or other outputs instead");
+            }
+        }
+
+        if self.record_dir.is_some() && self.replay_dir.is_some() {
+            bail!("can't record while replaying, use either record_dir or replay_dir");
+        }
+
+        if self.min_poll_interval < 5 {
+            bail!(
+                "min_poll_interval of {}s is too short, use at least 5 seconds to stay under the API rate limit",
+                self.min_poll_interval
+            );
+        }
+
+        if self.poll_interval < self.min_poll_interval {
+            bail!(
+                "poll_interval of {}s can't be shorter than the min_poll_interval of {}s",
+                self.poll_interval,
+                self.min_poll_interval
+            );
+        }
+
+        if self.max_poll_interval < self.poll_interval {
+            bail!(
+                "max_poll_interval of {}s can't be shorter than the poll_interval of {}s",
+                self.max_poll_interval,
+                self.poll_interval
+            );
+        }
+
+        if !REGIONS.contains(&self.region.as_str()) {
+            bail!(
+                "unknown region '{}', expected one of {}",
+                self.region,
+                REGIONS.join(", ")
+            );
+        }
+
+        if !QUEUES.contains(&self.queue.as_str()) {
+            bail!(
+                "unknown queue '{}', expected one of {}",
+                self.queue,
+                QUEUES.join(", ")
+            );
+        }
+
+        if !self.base_url.starts_with("http://") && !self.base_url.starts_with("https://") {
+            bail!(
+                "base_url '{}' should start with http:// or https://",
+                self.base_url
+            );
+        }
+
+        if self.win_streak < 0 || self.loss_streak < 0 {
+            bail!("win_streak and loss_streak can't be negative, use 0 to disable them");
+        }
+
+        // Idle players are only polled every max_poll_interval, so a poller waiting on them would look stuck
+        if self.stale_after <= self.max_poll_interval {
+            bail!(
+                "stale_after of {}s has to be longer than the max_poll_interval of {}s",
+                self.stale_after,
+                self.max_poll_interval
+            );
+        }
+
+        // Any longer and the poller would get the same response two polls in a row
+        if self.cache_ttl >= self.min_poll_interval {
+            bail!(
+                "cache_ttl of {}s has to be shorter than the min_poll_interval of {}s",
+                self.cache_ttl,
+                self.min_poll_interval
+            );
+        }
+
+        EnvFilter::try_new(&self.log_filter)
+            .with_context(|| format!("invalid log_filter '{}'", self.log_filter))?;
+
+        if !LOG_FORMATS.contains(&self.log_format.as_str()) {
+            bail!(
+                "unknown log_format '{}', expected one of {}",
+                self.log_format,
+                LOG_FORMATS.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The guild made from the players file and these settings.
+    pub fn default_guild(&self, players: Vec<PlayerData>) -> GuildConfig {
+        let mut guild = GuildConfig {
+            players,
+            game_channel: self.game_channel,
+            mmr_channel: self.mmr_channel,
+            game_webhook: self.game_webhook.clone(),
+            mmr_webhook: self.mmr_webhook.clone(),
+            outputs: self.outputs.clone(),
+            settings: GuildSettings::default(),
+        };
+
+        // Lets a dry run try out a players file without setting up any outputs
+        if self.dry_run {
+            let missing = [
+                (Feed::Game, guild.game_outputs().is_empty()),
+                (Feed::Mmr, guild.mmr_outputs().is_empty()),
+            ];
+
+            for (feed, _) in missing.into_iter().filter(|(_, missing)| *missing) {
+                guild.outputs.push(FeedOutput {
+                    feed,
+                    output: Output::Console,
+                });
+            }
+        }
+
+        guild
+    }
+
+    pub fn min_poll_interval(&self) -> Duration {
+        Duration::from_secs(self.min_poll_interval)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval)
+    }
+
+    pub fn max_poll_interval(&self) -> Duration {
+        Duration::from_secs(self.max_poll_interval)
+    }
+
+    pub fn session_gap(&self) -> Duration {
+        Duration::from_secs(self.session_gap * 60)
+    }
+
+    pub fn stale_after(&self) -> Duration {
+        Duration::from_secs(self.stale_after)
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_ttl)
+    }
+
+    pub fn trade_window(&self) -> Duration {
+        Duration::from_secs(self.trade_window)
+    }
+
+    pub fn streak_thresholds(&self) -> StreakThresholds {
+        StreakThresholds {
+            win: self.win_streak,
+            loss: self.loss_streak,
+        }
+    }
+}
+
+fn read_file(path: &PathBuf) -> Result<FileConfig> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("couldn't read config file {}", path.display()))?;
+
+    toml::from_str(&contents).with_context(|| format!("invalid config file {}", path.display()))
+}
+
+fn read_token(path: &PathBuf) -> Result<String> {
+    std::fs::read_to_string(path)
+        .with_context(|| format!("couldn't read token file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    fn temp_file(contents: &str) -> PathBuf {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "valorant_tracker_config_{}_{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Loads a config file with `file` in it, `flags` on top, and `env` as DISCORD_TOKEN and DISCORD_TOKEN_FILE.
+    fn load(file: &str, flags: &[&str], env: (Option<&str>, Option<&Path>)) -> Result<Config> {
+        let file = temp_file(file);
+        let mut args = Args::parse_from(
+            ["valorant_tracker", "--config", file.to_str().unwrap()]
+                .iter()
+                .chain(flags),
+        );
+        args.env_token = env.0.map(str::to_string);
+        args.env_token_file = env.1.map(Path::to_path_buf);
+
+        let config = Config::from_args(args);
+        std::fs::remove_file(file).unwrap();
+        config
+    }
+
+    #[test]
+    fn flags_beat_the_environment_which_beats_the_file() {
+        let flag_file = temp_file("from flag file\n");
+        let env_file = temp_file("from env file\n");
+        let file_file = temp_file("from file file\n");
+        let flag_path = flag_file.to_str().unwrap();
+        let file = |token: bool, token_file: bool| {
+            let mut file = String::new();
+            if token {
+                file += "token = \"from file\"\n";
+            }
+            if token_file {
+                file += &format!("token_file = {:?}\n", file_file);
+            }
+            file
+        };
+
+        // (file, flags, env, token)
+        let cases = [
+            (
+                file(true, true),
+                vec!["--token", "from flag", "--token-file", flag_path],
+                (Some("from env"), Some(env_file.as_path())),
+                Some("from flag"),
+            ),
+            (
+                file(true, true),
+                vec!["--token-file", flag_path],
+                (Some("from env"), Some(env_file.as_path())),
+                Some("from flag file"),
+            ),
+            (
+                file(true, true),
+                vec![],
+                (Some("from env"), Some(env_file.as_path())),
+                Some("from env"),
+            ),
+            (
+                file(true, true),
+                vec![],
+                (None, Some(env_file.as_path())),
+                Some("from env file"),
+            ),
+            (file(true, true), vec![], (None, None), Some("from file")),
+            (
+                file(false, true),
+                vec![],
+                (None, None),
+                Some("from file file"),
+            ),
+            (file(false, false), vec![], (None, None), None),
+        ];
+
+        for (file, mut flags, env, token) in cases {
+            flags.push("--dry-run");
+            let config = load(&file, &flags, env).unwrap();
+            assert_eq!(config.token.as_deref(), token, "{flags:?} {env:?}");
+        }
+
+        for path in [flag_file, env_file, file_file] {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        let region = |file, flags: &[&str]| load(file, flags, (None, None)).unwrap().region;
+        assert_eq!(
+            region("dry_run = true\nregion = \"EU\"", &["--region", "kr"]),
+            "kr"
+        );
+        assert_eq!(region("dry_run = true\nregion = \"EU\"", &[]), "eu");
+        assert_eq!(region("dry_run = true", &[]), "na");
+    }
+
+    #[test]
+    fn defaults_follow_the_poll_intervals() {
+        let config = load("dry_run = true", &[], (None, None)).unwrap();
+        assert_eq!(
+            (
+                config.min_poll_interval,
+                config.poll_interval,
+                config.max_poll_interval
+            ),
+            (30, 60, 900)
+        );
+        assert_eq!((config.cache_ttl, config.stale_after), (15, 1800));
+
+        let config = load(
+            "dry_run = true\npoll_interval = 10\nmax_poll_interval = 60",
+            &[],
+            (None, None),
+        )
+        .unwrap();
+        assert_eq!(config.min_poll_interval, 10);
+        assert_eq!((config.cache_ttl, config.stale_after), (5, 600));
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let json_lines =
+            "[[outputs]]\nfeed = \"game\"\nkind = \"json_lines\"\npath = \"games.jsonl\"";
+
+        // (file, flags, error)
+        let cases = [
+            ("", vec![], "no Discord token given"),
+            (
+                json_lines,
+                vec!["--admin-channel", "1"],
+                "posting to channels needs a Discord token",
+            ),
+            (
+                "[[outputs]]\nfeed = \"mmr\"\nkind = \"json_webhook\"\nurl = \"ftp://example.com\"",
+                vec!["--dry-run"],
+                "JSON webhook URL 'ftp://example.com' should start with http:// or https://",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--record-dir", "a", "--replay-dir", "b"],
+                "can't record while replaying",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--min-poll-interval", "4"],
+                "min_poll_interval of 4s is too short",
+            ),
+            (
+                "",
+                vec![
+                    "--dry-run",
+                    "--min-poll-interval",
+                    "30",
+                    "--poll-interval",
+                    "20",
+                ],
+                "poll_interval of 20s can't be shorter than the min_poll_interval of 30s",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--max-poll-interval", "30"],
+                "max_poll_interval of 30s can't be shorter than the poll_interval of 60s",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--region", "mars"],
+                "unknown region 'mars'",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--queue", "ranked"],
+                "unknown queue 'ranked'",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--base-url", "ftp://example.com"],
+                "base_url 'ftp://example.com'",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--win-streak=-1"],
+                "can't be negative",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--stale-after", "900"],
+                "stale_after of 900s has to be longer than the max_poll_interval of 900s",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--cache-ttl", "30"],
+                "cache_ttl of 30s has to be shorter than the min_poll_interval of 30s",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--log-filter", "a=b=c"],
+                "invalid log_filter 'a=b=c'",
+            ),
+            (
+                "",
+                vec!["--dry-run", "--log-format", "xml"],
+                "unknown log_format 'xml'",
+            ),
+        ];
+
+        for (file, flags, error) in cases {
+            let e = load(file, &flags, (None, None)).unwrap_err();
+            assert!(format!("{e:#}").contains(error), "{flags:?}: {e:#}");
+        }
+    }
+}
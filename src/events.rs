@@ -0,0 +1,111 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+use crate::{MatchDatum, MmrDatum, PlayerData};
+
+/// Plenty for a round of polling, a reporter this far behind skips ahead
+const CAPACITY: usize = 1024;
+
+/// What the poller noticed, reporters pick out the ones they care about.
+// Not every event has a reporter yet
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// A tracked player finished a match since the last poll, with their MMR change if it was new
+    NewMatch {
+        player: PlayerData,
+        game: Arc<MatchDatum>,
+        mmr: Option<MmrDatum>,
+    },
+    /// A player's elo moved, or it was seen for the first time
+    MmrChanged {
+        player: PlayerData,
+        previous: Option<MmrDatum>,
+        current: MmrDatum,
+    },
+    RankChanged {
+        player: PlayerData,
+        from: String,
+        to: String,
+    },
+    /// A known puuid showed up as a different `Name#Tag`
+    PlayerRenamed {
+        puuid: String,
+        from: String,
+        to: String,
+    },
+    ApiError {
+        player: PlayerData,
+        endpoint: &'static str,
+        error: String,
+    },
+    /// Every player was polled, `at` is the poller's clock in unix seconds
+    PollFinished { at: i64 },
+    /// Sent once the poller is done for good, nothing comes after it
+    Stopped,
+}
+
+/// Broadcasts events from the poller to every reporter, cheap to clone.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: Sender<Event>,
+    /// Latest `MmrChanged` of every player, for reporters that start after it was sent
+    mmrs: Arc<Mutex<HashMap<PlayerData, MmrDatum>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(CAPACITY).0,
+            mmrs: Arc::default(),
+        }
+    }
+
+    pub fn emit(&self, event: Event) {
+        if let Event::MmrChanged {
+            player, current, ..
+        } = &event
+        {
+            self.mmrs
+                .lock()
+                .unwrap()
+                .insert(player.clone(), current.clone());
+        }
+
+        // Only fails when nobody is subscribed, in which case nobody cares
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribes now, so a reporter that takes a moment to start doesn't miss anything.
+    pub fn subscription(&self) -> Subscription {
+        Subscription {
+            bus: self.clone(),
+            first: Mutex::new(Some(self.sender.subscribe())),
+        }
+    }
+}
+
+/// Receivers for a reporter, one per time the supervisor starts it.
+pub struct Subscription {
+    bus: EventBus,
+    first: Mutex<Option<Receiver<Event>>>,
+}
+
+impl Subscription {
+    pub fn receiver(&self) -> Receiver<Event> {
+        self.first
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_else(|| self.bus.sender.subscribe())
+    }
+
+    /// Every player's MMR as of now, take it after `receiver` so no change falls in between.
+    pub fn mmrs(&self) -> HashMap<PlayerData, MmrDatum> {
+        self.bus.mmrs.lock().unwrap().clone()
+    }
+}
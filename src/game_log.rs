@@ -0,0 +1,740 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    agents::AgentTotals,
+    config::Config,
+    events::Event,
+    guilds::{Guilds, SharedGuilds},
+    head_to_head::{most_by, tally_match, OpponentRecord},
+    health::HEALTH,
+    hendrix_mmr_response::MmrDatum,
+    match_ranking::MatchRanking,
+    metrics::METRICS,
+    notifier::NotifierContext,
+    output::Output,
+    party::Party,
+    records::RecordCandidates,
+    report::{Card, CardField, MatchReport, ReportPlayer, SessionReport},
+    round_analysis::analyze,
+    session::{Session, SessionGame},
+    store::{SharedStore, Store},
+    MatchDatum, Player, PlayerData, TeamEnum,
+};
+
+pub const NAME: &str = "Game log";
+
+/// Posts every new match and session recap.
+pub async fn game_log_thread(
+    guilds: SharedGuilds,
+    mut events: Receiver<Event>,
+    notifiers: NotifierContext,
+    store: SharedStore,
+    config: Arc<Config>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(Event::NewMatch { player, game, mmr }) => {
+                let guilds = guilds.read().await.clone();
+                report_match(&player, &game, mmr, &guilds, &notifiers, &store, &config).await;
+            }
+            Ok(Event::PollFinished { at }) => {
+                HEALTH.polled(NAME);
+
+                let guilds = guilds.read().await.clone();
+                let tracked = guilds.players_where(|g| !g.game_outputs().is_empty());
+
+                for (player, session) in ended_sessions(&store, &tracked, at, &config).await {
+                    let outputs = outputs(&guilds, &player, &config);
+                    send_session_recap(&notifiers, &outputs, &player, &session).await;
+                }
+            }
+            Ok(Event::Stopped) | Err(RecvError::Closed) => break,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(skipped, "Game log fell behind, some events were skipped");
+            }
+            Ok(_) => {}
+        }
+    }
+
+    // Open sessions stay in the store, so they carry on after a restart instead of being recapped early
+
+    info!("Game log stopped.");
+}
+
+/// Takes every session out of the store whose gap has passed by `now`, dropping those of players nobody tracks.
+async fn ended_sessions(
+    store: &SharedStore,
+    tracked: &[PlayerData],
+    now: i64,
+    config: &Config,
+) -> Vec<(PlayerData, Session)> {
+    let mut store = store.lock().await;
+    let Store {
+        sessions, players, ..
+    } = &mut *store;
+
+    let before = sessions.len();
+    let tracked_player = |puuid: &String| {
+        let player = PlayerData::parse(players.get(puuid)?)?;
+        tracked.iter().find(|t| **t == player).cloned()
+    };
+    sessions.retain(|puuid, _| tracked_player(puuid).is_some());
+
+    let over = sessions
+        .iter()
+        .filter(|(_, s)| s.is_over(now, config.session_gap()))
+        .filter_map(|(puuid, _)| Some((puuid.clone(), tracked_player(puuid)?)))
+        .collect::<Vec<(String, PlayerData)>>();
+
+    let ended = over
+        .into_iter()
+        .filter_map(|(puuid, player)| Some((player, sessions.remove(&puuid)?)))
+        .collect::<Vec<(PlayerData, Session)>>();
+
+    if sessions.len() != before {
+        store.save().await;
+    }
+
+    ended
+}
+
+fn outputs(guilds: &Guilds, player: &PlayerData, config: &Config) -> Vec<Output> {
+    guilds
+        .game_targets(player, config.streak_thresholds())
+        .into_iter()
+        .map(|(o, _)| o)
+        .collect()
+}
+
+/// Posts a new match to every guild tracking the player, and adds it to their stats and session.
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(player = %id, match_id = %game.metadata.match_id))]
+async fn report_match(
+    id: &PlayerData,
+    game: &MatchDatum,
+    new_mmr: Option<MmrDatum>,
+    guilds: &Guilds,
+    notifiers: &NotifierContext,
+    store: &SharedStore,
+    config: &Config,
+) {
+    let tracked = guilds.players_where(|_| true);
+    let targets = guilds.game_targets(id, config.streak_thresholds());
+    let outputs = targets
+        .iter()
+        .map(|(o, _)| o.clone())
+        .collect::<Vec<Output>>();
+
+    let metadata = &game.metadata;
+
+    // Already found by the poller
+    let Some(player) = game
+        .players
+        .all_players
+        .iter()
+        .find(|p| id.is(&p.name, &p.tag))
+    else {
+        return;
+    };
+
+    let player_stats = &player.stats;
+
+    let rounds = game.rounds.len() as i64;
+
+    let ranking = MatchRanking::of(game);
+    let rank = ranking.get(&player.puuid).cloned().unwrap(); // Should NEVER fail, the player was found in the same list
+
+    // this is cancerous but not really a better way to do this that doesn't require just moving it into the other file
+    let player_team = if player.team == TeamEnum::Red {
+        &game.teams.red
+    } else {
+        &game.teams.blue
+    };
+
+    let mut fields = vec![
+        field("Map", &metadata.map),
+        field("Rounds", metadata.rounds_played),
+        field(
+            "Rounds Won / Lost",
+            format!("{} / {}", player_team.rounds_won, player_team.rounds_lost),
+        ),
+        field(
+            "Game Length",
+            format!("{}min", metadata.game_length / 60000),
+        ),
+        field("Agent", &player.character),
+        field(
+            "K / A / D",
+            format!(
+                "{} / {} / {}",
+                player_stats.kills, player_stats.assists, player_stats.deaths
+            ),
+        ),
+        field(
+            "KD / KAD",
+            format!(
+                "{} / {}",
+                calculate_kd(player, false),
+                calculate_kd(player, true)
+            ),
+        ),
+        field(
+            "Lobby / Team Position",
+            format!(
+                "{} / {}",
+                ordinal(rank.lobby_position),
+                ordinal(rank.team_position)
+            ),
+        ),
+        field(
+            "Head Shots",
+            calculate_headshot_percentage(player)
+                .map(|p| format!("{}%", p as i64))
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        field("Avg. Combat Score", format!("{:.0}", rank.acs)),
+    ];
+
+    if let Some(playtime) = player.session_playtime.minutes {
+        fields.push(field("Session Playtime", format!("{}min", playtime)));
+    }
+
+    let behavior = &player.behavior;
+    if behavior.afk_rounds > 0_f64 {
+        fields.push(field("AFK Rounds", behavior.afk_rounds));
+    }
+
+    if behavior.rounds_in_spawn > 0_f64 {
+        fields.push(field("Rounds in Spawn", behavior.rounds_in_spawn));
+    }
+
+    if player.party_id.is_some() {
+        let partied_with = game
+            .players
+            .all_players
+            .iter()
+            .filter(|p| p.party_id == player.party_id && p.name != player.name)
+            .map(|p| format!("{}#{}", p.name, p.tag))
+            .collect::<Vec<String>>();
+
+        if !partied_with.is_empty() {
+            fields.push(field("Partied With", partied_with.join(", ")))
+        }
+    }
+
+    // A draw neither wins nor loses
+    let won = if player_team.has_won {
+        Some(true)
+    } else if player_team.rounds_won == player_team.rounds_lost {
+        None
+    } else {
+        Some(false)
+    };
+
+    // The previous session is usually posted while idle, but it could still be here if the bot was busy
+    let acs = player_stats.score as f64 / rounds.max(1) as f64;
+    let previous = store.lock().await.sessions.remove(&player.puuid);
+    let mut session = match previous {
+        Some(s) if s.continues_with(metadata.game_start, config.session_gap()) => s,
+        Some(s) => {
+            send_session_recap(notifiers, &outputs, id, &s).await;
+            Session::new(metadata.game_start)
+        }
+        None => Session::new(metadata.game_start),
+    };
+
+    session.add(
+        metadata.game_start,
+        metadata.game_length,
+        won,
+        new_mmr.as_ref().map(|m| m.mmr_change_to_last_game),
+        SessionGame {
+            map: metadata.map.clone(),
+            agent: player.character.clone(),
+            acs,
+            kills: player_stats.kills,
+            deaths: player_stats.deaths,
+            assists: player_stats.assists,
+        },
+    );
+
+    let mut store = store.lock().await;
+    store.sessions.insert(player.puuid.clone(), session);
+
+    store.party.entry(player.puuid.clone()).or_default().record(
+        &Party::of(game, player, &tracked),
+        player_team.has_won,
+        new_mmr.as_ref().map(|m| m.mmr_change_to_last_game),
+        acs,
+    );
+
+    store
+        .agents
+        .entry(player.puuid.clone())
+        .or_default()
+        .record(
+            &player.character,
+            &metadata.map,
+            AgentTotals {
+                games: 1,
+                wins: player_team.has_won as i64,
+                kills: player_stats.kills,
+                deaths: player_stats.deaths,
+                score: player_stats.score,
+                rounds,
+            },
+        );
+
+    let broken_records = store
+        .records
+        .entry(player.puuid.clone())
+        .or_default()
+        .update(
+            RecordCandidates {
+                acs,
+                kills: player_stats.kills as f64,
+                headshot_percentage: calculate_headshot_percentage(player),
+                adr: player.damage_made as f64 / rounds.max(1) as f64,
+                rr_gain: new_mmr.as_ref().map(|m| m.mmr_change_to_last_game as f64),
+            },
+            &metadata.match_id,
+            &metadata.map,
+            &player.character,
+            metadata.game_start,
+        );
+
+    let streak = store.streaks.entry(player.puuid.clone()).or_default();
+    streak.record(won);
+
+    let streak = streak.clone();
+    fields.push(field("Streak", &streak));
+
+    let contribution = analyze(game, &player.puuid, config.trade_window());
+    let all_time_contribution = store
+        .round_contributions
+        .entry(player.puuid.clone())
+        .or_default();
+    all_time_contribution.add(&contribution);
+
+    fields.push(field(
+        "KAST (All Time)",
+        format!(
+            "{:.0}% ({:.0}%)",
+            contribution.kast(),
+            all_time_contribution.kast()
+        ),
+    ));
+    fields.push(field(
+        "Trade Kills / Traded Deaths",
+        format!(
+            "{} / {}",
+            contribution.trade_kills, contribution.traded_deaths
+        ),
+    ));
+
+    let tally = tally_match(game, &player.puuid);
+    let head_to_head = store.head_to_head.entry(player.puuid.clone()).or_default();
+    head_to_head.record(&tally);
+
+    if let Some((puuid, matchup)) = most_by(&tally, |r| r.kills) {
+        fields.push(field(
+            "Killed Most",
+            matchup_summary(
+                matchup,
+                matchup.kills,
+                &head_to_head.opponents[puuid],
+                |r| r.kills,
+            ),
+        ));
+    }
+
+    if let Some((puuid, matchup)) = most_by(&tally, |r| r.deaths) {
+        fields.push(field(
+            "Died To Most",
+            matchup_summary(
+                matchup,
+                matchup.deaths,
+                &head_to_head.opponents[puuid],
+                |r| r.deaths,
+            ),
+        ));
+    }
+
+    store.save().await;
+    drop(store);
+
+    if let Some(mmr) = &new_mmr {
+        fields.append(&mut mmr_fields(mmr));
+    } else {
+        fields.push(field("Current Rank", &player.current_tier_patched))
+    }
+
+    let mut description = format!(
+        "{} **{}** their game on {} with a KD of {}, and is now at rank {}",
+        id.display_name(),
+        match won {
+            Some(true) => "won",
+            Some(false) => "lost",
+            None => "drew",
+        },
+        metadata.map,
+        calculate_kd(player, false),
+        player.current_tier_patched
+    );
+
+    if let Some(badge) = rank.badge() {
+        description = format!("{badge}\n{description}");
+    }
+
+    if !broken_records.is_empty() {
+        description = format!(
+            "🏆 **New personal best!** {}\n\n{description}",
+            broken_records
+                .iter()
+                .map(|r| r.label())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        );
+    }
+
+    let report = MatchReport {
+        player: ReportPlayer::from(id),
+        match_id: metadata.match_id.clone(),
+        map: metadata.map.clone(),
+        agent: player.character.clone(),
+        streak_announcement: None,
+        card: Card {
+            title: format!("{}'s Game on {}", id.display_name(), metadata.map),
+            description,
+            won,
+            timestamp: metadata.game_start,
+            image: Some(player.assets.card.wide.clone()),
+            thumbnail: Some(player.assets.agent.small.clone()),
+            fields,
+        },
+    };
+
+    for (output, streak_thresholds) in targets {
+        let report = MatchReport {
+            streak_announcement: streak.announcement(id.display_name(), streak_thresholds),
+            ..report.clone()
+        };
+
+        let sent = match output.notifier(notifiers) {
+            Ok(n) => n.send_match(&report).await,
+            Err(e) => Err(e),
+        };
+
+        match sent {
+            Ok(()) => {
+                info!(%output, "Sent new match message");
+                METRICS.matches_posted.inc();
+            }
+            Err(e) => {
+                error!(%output, "Failed to send match message -> {e:#}");
+                METRICS.send_failures.with_label_values(&["match"]).inc();
+            }
+        }
+    }
+}
+
+async fn send_session_recap(
+    notifiers: &NotifierContext,
+    outputs: &[Output],
+    id: &PlayerData,
+    session: &Session,
+) {
+    // A single game was already posted on its own, a recap wouldn't add anything
+    if session.games < 2 {
+        return;
+    }
+
+    let mut fields = vec![
+        field("Games", session.games),
+        if session.draws > 0 {
+            field(
+                "W / L / D",
+                format!("{} / {} / {}", session.wins, session.losses, session.draws),
+            )
+        } else {
+            field("W / L", format!("{} / {}", session.wins, session.losses))
+        },
+        field("Net RR", format!("{:+}", session.net_rr)),
+        field(
+            "Time Played",
+            format!(
+                "{}h {}min",
+                session.played_ms / 3_600_000,
+                session.played_ms / 60000 % 60
+            ),
+        ),
+    ];
+
+    if let Some(best) = &session.best {
+        fields.push(field(
+            "Best Game",
+            format!(
+                "{} on {}, {} / {} / {}, {:.0} ACS",
+                best.agent, best.map, best.kills, best.assists, best.deaths, best.acs
+            ),
+        ));
+    }
+
+    let report = SessionReport {
+        player: ReportPlayer::from(id),
+        games: session.games,
+        wins: session.wins,
+        losses: session.losses,
+        draws: session.draws,
+        net_rr: session.net_rr,
+        played_ms: session.played_ms,
+        card: Card {
+            title: format!("{}'s Session Recap", id.display_name()),
+            won: Some(session.wins >= session.losses),
+            timestamp: session.started,
+            fields,
+            ..Card::default()
+        },
+    };
+
+    for output in outputs {
+        let sent = match output.notifier(notifiers) {
+            Ok(n) => n.send_session(&report).await,
+            Err(e) => Err(e),
+        };
+
+        match sent {
+            Ok(()) => info!(player = %id, %output, "Sent session recap"),
+            Err(e) => {
+                error!(player = %id, %output, "Failed to send session recap -> {e:#}");
+                METRICS.send_failures.with_label_values(&["session"]).inc();
+            }
+        }
+    }
+}
+
+fn mmr_fields(mmr: &MmrDatum) -> Vec<CardField> {
+    let op = if mmr.mmr_change_to_last_game > 0 {
+        "+"
+    } else {
+        ""
+    };
+
+    vec![
+        field("MMR", format!("{op}{}", mmr.mmr_change_to_last_game)),
+        field(
+            "Rank",
+            format!("{} @ {} MMR", mmr.current_tier_patched, mmr.ranking_in_tier),
+        ),
+    ]
+}
+
+fn calculate_kd(player: &Player, assists: bool) -> String {
+    let mut kills = player.stats.kills;
+
+    if assists {
+        kills += player.stats.assists;
+    }
+
+    format!("{:.2}", kills as f64 / player.stats.deaths as f64)
+}
+
+/// None if they never hit a shot, there's no percentage to show then.
+fn calculate_headshot_percentage(player: &Player) -> Option<f64> {
+    let all_shots = player.stats.head_shots + player.stats.body_shots + player.stats.leg_shots;
+
+    if all_shots == 0 {
+        return None;
+    }
+
+    Some((player.stats.head_shots as f64 / all_shots as f64) * 100_f64)
+}
+
+/// Formats a single matchup as `Name#Tag (4)`, with the all time count appended if they've met before.
+fn matchup_summary<F>(
+    matchup: &OpponentRecord,
+    count: i64,
+    all_time: &OpponentRecord,
+    key: F,
+) -> String
+where
+    F: Fn(&OpponentRecord) -> i64,
+{
+    let mut summary = format!("{} ({count})", matchup.display_name);
+
+    if all_time.games > 1 {
+        summary = format!(
+            "{summary}, {} times across {} games",
+            key(all_time),
+            all_time.games
+        );
+    }
+
+    summary
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{n}{suffix}")
+}
+
+#[inline]
+fn field<A: ToString, B: ToString>(key: A, value: B) -> CardField {
+    CardField::new(key, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use serde_json::Value;
+    use tokio::time::timeout;
+
+    use crate::{
+        events::Event,
+        mock_henrik::MockHenrik,
+        session::{Session, SessionGame},
+        store::Store,
+        test_bot::Bot,
+        test_match::TestMatch,
+        PlayerData,
+    };
+
+    const ZERO_SHOT: &str = "6b0b2a0e-5c5e-5b52-9e3a-2c1e0f2f1a01";
+
+    /// Replays the zero_shots recording, where Zero#SHOT's second match is posted at 1700003200.
+    async fn replay(name: &str, store: Store) -> (Vec<Value>, Store) {
+        let replay_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/replay/zero_shots");
+        let bot = Bot::start_with_store(
+            name,
+            &format!("replay_dir = {replay_dir:?}"),
+            &["Zero#SHOT"],
+            store,
+        )
+        .await;
+        timeout(Duration::from_secs(10), bot.api.replay_finished())
+            .await
+            .unwrap();
+
+        let store = bot.store.clone();
+        let (reports, _) = bot.stop().await;
+        let store = std::mem::take(&mut *store.lock().await);
+        (reports, store)
+    }
+
+    /// What a previous run left behind, two games that ended at `ended`.
+    fn open_session(ended: i64) -> Store {
+        let mut session = Session::new(ended - 3600);
+        for start in [ended - 3600, ended - 1800] {
+            let game = SessionGame {
+                map: "Ascent".to_string(),
+                agent: "Sage".to_string(),
+                acs: 200.0,
+                kills: 15,
+                deaths: 15,
+                assists: 5,
+            };
+            session.add(start, 1_800_000, Some(true), Some(20), game);
+        }
+
+        let mut store = Store::default();
+        store
+            .players
+            .insert(ZERO_SHOT.to_string(), "Zero#SHOT".to_string());
+        store.sessions.insert(ZERO_SHOT.to_string(), session);
+        store
+    }
+
+    #[tokio::test]
+    async fn match_without_shots_has_no_headshot_percentage() {
+        let (reports, _) = replay("zero_shots", Store::default()).await;
+
+        // The first match is only remembered, the second one gets posted
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["type"], "match");
+
+        let fields = reports[0]["card"]["fields"].as_array().unwrap();
+        let head_shots = fields.iter().find(|f| f["name"] == "Head Shots").unwrap();
+        assert_eq!(head_shots["value"], "-");
+        assert!(!reports[0].to_string().contains("NaN"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn posts_a_draw_without_rounds_as_a_draw() {
+        // Nothing to poll, the match is sent straight to the game log
+        let henrik = MockHenrik::start().await;
+        let mut store = Store::default();
+        store
+            .players
+            .insert(ZERO_SHOT.to_string(), "Zero#SHOT".to_string());
+        let bot = Bot::start_with_store(
+            "draw",
+            &format!("base_url = {:?}", henrik.base_url),
+            &["Zero#SHOT"],
+            store,
+        )
+        .await;
+
+        let game = TestMatch::new()
+            .player(ZERO_SHOT, "Red", [300, 3, 2, 1])
+            .score(0, 0)
+            // Recent enough that the session is still open when stopping
+            .started(bot.api.now() - 60)
+            .build();
+        bot.emit(Event::NewMatch {
+            player: PlayerData::parse("Zero#SHOT").unwrap(),
+            game: Arc::new(game),
+            mmr: None,
+        });
+        let reports = bot.wait_for("game", 1).await;
+
+        let card = &reports[0]["card"];
+        assert_eq!(card["won"], Value::Null);
+        assert!(card["description"].as_str().unwrap().contains("**drew**"));
+        assert!(!reports[0].to_string().contains("NaN"));
+
+        let store = bot.store.clone();
+        bot.stop().await;
+        let store = store.lock().await;
+        let session = &store.sessions[ZERO_SHOT];
+        assert_eq!((session.wins, session.losses, session.draws), (0, 0, 1));
+        assert_eq!(session.best.as_ref().unwrap().acs, 300.0);
+        // One zero round match mustn't leave /synergy at NaN for good
+        assert_eq!(store.party[ZERO_SHOT].by_size[&1].acs, 300.0);
+    }
+
+    #[tokio::test]
+    async fn recaps_a_restored_session_once_the_gap_passes() {
+        // Well over the 30 minute gap before the first poll at 1700002000
+        let (reports, store) = replay("session_over", open_session(1_699_990_000)).await;
+
+        let types = reports
+            .iter()
+            .map(|r| r["type"].clone())
+            .collect::<Vec<Value>>();
+        assert_eq!(types, ["session", "match"]);
+        assert_eq!(reports[0]["games"], 2);
+
+        // The new match starts a session of its own
+        assert_eq!(store.sessions[ZERO_SHOT].games, 1);
+    }
+
+    #[tokio::test]
+    async fn open_sessions_carry_on_after_stopping() {
+        // Still going when the new match starts
+        let (reports, store) = replay("session_open", open_session(1_700_001_500)).await;
+
+        assert!(reports.iter().all(|r| r["type"] == "match"));
+        assert_eq!(store.sessions[ZERO_SHOT].games, 3);
+    }
+}
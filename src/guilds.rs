@@ -0,0 +1,150 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::error;
+
+use crate::{
+    output::{Feed, FeedOutput, Output},
+    store::{load_json, write_atomically},
+    streak::StreakThresholds,
+    PlayerData,
+};
+
+pub const GUILDS_FILE: &str = "./guilds.json";
+
+pub type SharedGuilds = Arc<RwLock<Guilds>>;
+
+/// Every guild the bot posts to, keyed by guild id.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Guilds {
+    /// Built from the players file and channel arguments, never saved
+    #[serde(skip)]
+    pub default: Option<GuildConfig>,
+    #[serde(default)]
+    pub guilds: HashMap<u64, GuildConfig>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct GuildConfig {
+    #[serde(default)]
+    pub players: Vec<PlayerData>,
+    pub game_channel: Option<u64>,
+    pub mmr_channel: Option<u64>,
+    /// Webhook URLs posted to alongside, or instead of, the channels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_webhook: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmr_webhook: Option<String>,
+    /// Any other outputs, ex. JSON webhooks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<FeedOutput>,
+    #[serde(default)]
+    pub settings: GuildSettings,
+}
+
+impl GuildConfig {
+    pub fn game_outputs(&self) -> Vec<Output> {
+        self.outputs(Feed::Game, self.game_channel, &self.game_webhook)
+    }
+
+    pub fn mmr_outputs(&self) -> Vec<Output> {
+        self.outputs(Feed::Mmr, self.mmr_channel, &self.mmr_webhook)
+    }
+
+    fn outputs(&self, feed: Feed, channel: Option<u64>, webhook: &Option<String>) -> Vec<Output> {
+        channel
+            .map(Output::channel)
+            .into_iter()
+            .chain(webhook.as_deref().map(Output::discord_webhook))
+            .chain(
+                self.outputs
+                    .iter()
+                    .filter(|o| o.feed == feed)
+                    .map(|o| o.output.clone()),
+            )
+            .collect()
+    }
+}
+
+/// Per guild overrides, anything unset falls back to the command line arguments.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct GuildSettings {
+    pub win_streak: Option<i64>,
+    pub loss_streak: Option<i64>,
+}
+
+impl GuildSettings {
+    pub fn streak_thresholds(&self, default: StreakThresholds) -> StreakThresholds {
+        StreakThresholds {
+            win: self.win_streak.unwrap_or(default.win),
+            loss: self.loss_streak.unwrap_or(default.loss),
+        }
+    }
+}
+
+impl Guilds {
+    pub async fn load() -> Self {
+        load_json(GUILDS_FILE).await.unwrap_or_default()
+    }
+
+    pub async fn save(&self) {
+        let json = match serde_json::to_string_pretty(self) {
+            Ok(j) => j,
+            Err(e) => {
+                error!("Failed to serialize guilds -> {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = write_atomically(GUILDS_FILE, json.as_bytes()).await {
+            error!(file = GUILDS_FILE, "Failed to write guilds -> {e}");
+        }
+    }
+
+    pub fn configs(&self) -> impl Iterator<Item = &GuildConfig> {
+        self.default.iter().chain(self.guilds.values())
+    }
+
+    /// Every unique player in guilds matching `filter`, so each is only polled once.
+    pub fn players_where<F>(&self, filter: F) -> Vec<PlayerData>
+    where
+        F: Fn(&GuildConfig) -> bool,
+    {
+        let mut players = vec![];
+
+        for player in self
+            .configs()
+            .filter(|g| filter(g))
+            .flat_map(|g| &g.players)
+        {
+            if !players.contains(player) {
+                players.push(player.clone());
+            }
+        }
+
+        players
+    }
+
+    /// Game outputs of every guild tracking `player`, along with that guild's streak thresholds.
+    pub fn game_targets(
+        &self,
+        player: &PlayerData,
+        default_streaks: StreakThresholds,
+    ) -> Vec<(Output, StreakThresholds)> {
+        self.configs()
+            .filter(|g| g.players.contains(player))
+            .flat_map(|g| {
+                let streaks = g.settings.streak_thresholds(default_streaks);
+                g.game_outputs().into_iter().map(move |o| (o, streaks))
+            })
+            .collect()
+    }
+
+    /// MMR outputs of every guild with one, along with the players that guild tracks.
+    pub fn mmr_boards(&self) -> Vec<(Output, &Vec<PlayerData>)> {
+        self.configs()
+            .flat_map(|g| g.mmr_outputs().into_iter().map(|o| (o, &g.players)))
+            .collect()
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::MatchDatum;
+
+/// A player's all time record against every opponent they've shared a lobby with, keyed by the opponent's puuid.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct HeadToHead {
+    pub opponents: HashMap<String, OpponentRecord>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct OpponentRecord {
+    pub display_name: String,
+    pub kills: i64,
+    pub deaths: i64,
+    pub games: i64,
+}
+
+impl HeadToHead {
+    pub fn record(&mut self, tally: &HashMap<String, OpponentRecord>) {
+        for (puuid, game) in tally {
+            let record = self.opponents.entry(puuid.clone()).or_default();
+
+            // Names change, always keep the newest one
+            record.display_name = game.display_name.clone();
+            record.kills += game.kills;
+            record.deaths += game.deaths;
+            record.games += 1;
+        }
+    }
+}
+
+/// Counts the kills and deaths of `puuid` against every opponent in a single match.
+pub fn tally_match(game: &MatchDatum, puuid: &str) -> HashMap<String, OpponentRecord> {
+    let mut tally = HashMap::<String, OpponentRecord>::new();
+
+    for kill in &game.kills {
+        let (opponent, display_name) = if kill.killer_puuid == puuid {
+            (&kill.victim_puuid, &kill.victim_display_name)
+        } else if kill.victim_puuid == puuid {
+            (&kill.killer_puuid, &kill.killer_display_name)
+        } else {
+            continue;
+        };
+
+        // Suicides and team kills don't count towards a matchup
+        if opponent == puuid || kill.killer_team == kill.victim_team {
+            continue;
+        }
+
+        let record = tally.entry(opponent.clone()).or_default();
+        record.display_name = display_name.clone();
+        record.games = 1;
+
+        if kill.killer_puuid == puuid {
+            record.kills += 1;
+        } else {
+            record.deaths += 1;
+        }
+    }
+
+    tally
+}
+
+/// Returns the puuid and record of the opponent with the highest value of `key`, ignoring zeroes.
+pub fn most_by<F>(
+    tally: &HashMap<String, OpponentRecord>,
+    key: F,
+) -> Option<(&String, &OpponentRecord)>
+where
+    F: Fn(&OpponentRecord) -> i64,
+{
+    tally
+        .iter()
+        .filter(|(_, r)| key(r) > 0)
+        // Tie break on puuid so the same match always picks the same opponent
+        .max_by(|(ap, ar), (bp, br)| key(ar).cmp(&key(br)).then_with(|| bp.cmp(ap)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_match::TestMatch;
+
+    const ME: (&str, &str) = ("me", "Red");
+    const TEAMMATE: (&str, &str) = ("teammate", "Red");
+    const ALICE: (&str, &str) = ("alice", "Blue");
+    const BOB: (&str, &str) = ("bob", "Blue");
+
+    fn game() -> MatchDatum {
+        TestMatch::new()
+            .player("me", "Red", [0, 0, 0, 0])
+            .rounds(4)
+            .kill(0, 1000, ME, ALICE, &[])
+            .kill(1, 1000, ME, ALICE, &[])
+            .kill(1, 2000, ALICE, ME, &[])
+            .kill(2, 1000, BOB, ME, &[])
+            // Neither of these are a matchup
+            .kill(3, 1000, TEAMMATE, ME, &[])
+            .kill(3, 2000, ME, ME, &[])
+            // Nor is one we weren't in
+            .kill(3, 3000, TEAMMATE, BOB, &[])
+            .build()
+    }
+
+    #[test]
+    fn tallies_kills_and_deaths_against_opponents_only() {
+        let tally = tally_match(&game(), "me");
+
+        let mut counts = tally
+            .iter()
+            .map(|(puuid, r)| (puuid.as_str(), r.kills, r.deaths, r.games))
+            .collect::<Vec<_>>();
+        counts.sort();
+        assert_eq!(counts, [("alice", 2, 1, 1), ("bob", 0, 1, 1)]);
+    }
+
+    #[test]
+    fn picks_the_most_and_breaks_ties_by_puuid() {
+        let tally = tally_match(&game(), "me");
+
+        assert_eq!(most_by(&tally, |r| r.kills).unwrap().0, "alice");
+        // Alice and Bob both killed me once
+        assert_eq!(most_by(&tally, |r| r.deaths).unwrap().0, "alice");
+        assert!(most_by(&tally, |r| r.games - 1).is_none());
+
+        // Same winner no matter what order the tally is in
+        for _ in 0..20 {
+            let tally = tally.clone().into_iter().collect::<HashMap<_, _>>();
+            assert_eq!(most_by(&tally, |r| r.deaths).unwrap().0, "alice");
+        }
+    }
+
+    #[test]
+    fn adds_up_games_across_matches() {
+        let mut head_to_head = HeadToHead::default();
+        head_to_head.record(&tally_match(&game(), "me"));
+        head_to_head.record(&tally_match(&game(), "me"));
+
+        let alice = &head_to_head.opponents["alice"];
+        assert_eq!((alice.kills, alice.deaths, alice.games), (4, 2, 2));
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+/// What the status server's /healthz and /readyz report, global like the metrics.
+#[derive(Default)]
+pub struct Health {
+    gateway_connected: AtomicBool,
+    /// Set in webhook only mode, where there's no gateway to wait for
+    no_gateway: AtomicBool,
+    trackers: Mutex<HashMap<&'static str, TrackerHealth>>,
+}
+
+struct TrackerHealth {
+    alive: bool,
+    started: Instant,
+    last_poll: Option<Instant>,
+}
+
+pub static HEALTH: LazyLock<Health> = LazyLock::new(Health::default);
+
+impl Health {
+    pub fn set_gateway_connected(&self, connected: bool) {
+        self.gateway_connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn without_gateway(&self) {
+        self.no_gateway.store(true, Ordering::Relaxed);
+    }
+
+    /// Called by the supervisor whenever a tracker starts or stops running.
+    pub fn set_alive(&self, tracker: &'static str, alive: bool) {
+        let mut trackers = self.trackers.lock().unwrap();
+        let health = trackers.entry(tracker).or_insert(TrackerHealth {
+            alive,
+            started: Instant::now(),
+            last_poll: None,
+        });
+
+        if alive && !health.alive {
+            health.started = Instant::now();
+        }
+        health.alive = alive;
+    }
+
+    /// A tracker got a good HenrikDev response, or had no one to poll.
+    pub fn polled(&self, tracker: &'static str) {
+        if let Some(health) = self.trackers.lock().unwrap().get_mut(tracker) {
+            health.last_poll = Some(Instant::now());
+        }
+    }
+
+    /// Healthy if every tracker is running and has polled within `stale_after`.
+    /// Trackers get `stale_after` after starting before they need a first poll.
+    pub fn healthz(&self, stale_after: Duration) -> (bool, Value) {
+        self.report(stale_after, false)
+    }
+
+    /// Ready once the gateway is connected and every tracker is running with a recent poll.
+    pub fn readyz(&self, stale_after: Duration) -> (bool, Value) {
+        self.report(stale_after, true)
+    }
+
+    fn report(&self, stale_after: Duration, ready: bool) -> (bool, Value) {
+        let gateway = Some(self.gateway_connected.load(Ordering::Relaxed))
+            .filter(|_| !self.no_gateway.load(Ordering::Relaxed));
+        let mut ok = gateway.unwrap_or(true) || !ready;
+        let mut trackers = serde_json::Map::new();
+
+        for (name, health) in self.trackers.lock().unwrap().iter() {
+            let since_poll = health.last_poll.map(|p| p.elapsed());
+            let fresh = match since_poll {
+                Some(since) => since <= stale_after,
+                None => !ready && health.started.elapsed() <= stale_after,
+            };
+
+            ok &= health.alive && fresh;
+            trackers.insert(
+                name.to_string(),
+                json!({
+                    "alive": health.alive,
+                    "seconds_since_poll": since_poll.map(|s| s.as_secs()),
+                    "ok": health.alive && fresh,
+                }),
+            );
+        }
+
+        (
+            ok,
+            json!({ "ok": ok, "gateway_connected": gateway, "trackers": trackers }),
+        )
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{error, info, warn};
+
+use crate::{
+    events::Event,
+    guilds::SharedGuilds,
+    health::HEALTH,
+    metrics::METRICS,
+    notifier::NotifierContext,
+    output::Output,
+    report::{LeaderboardEntry, LeaderboardReport, ReportPlayer},
+    MmrDatum, PlayerData,
+};
+
+pub const NAME: &str = "MMR leaderboard";
+
+/// Keeps every guild's leaderboard up to date with the MMR changes the poller finds.
+/// `mmrs` are the ones already found, so a restarted reporter doesn't have to wait for everyone's elo to change.
+pub async fn leaderboard_thread(
+    guilds: SharedGuilds,
+    mut events: Receiver<Event>,
+    mut mmrs: HashMap<PlayerData, MmrDatum>,
+    notifiers: NotifierContext,
+) {
+    // Boards posted since starting, the rest only get updated when someone on them changes
+    let mut posted = HashSet::<Output>::new();
+    let mut changed = HashSet::<PlayerData>::new();
+
+    loop {
+        match events.recv().await {
+            Ok(Event::MmrChanged {
+                player, current, ..
+            }) => {
+                changed.insert(player.clone());
+                mmrs.insert(player, current);
+            }
+            Ok(Event::PollFinished { .. }) => {
+                HEALTH.polled(NAME);
+
+                let guilds = guilds.read().await.clone();
+                let players = guilds.players_where(|g| !g.mmr_outputs().is_empty());
+                mmrs.retain(|p, _| players.contains(p));
+
+                for (output, guild_players) in guilds.mmr_boards() {
+                    if posted.contains(&output)
+                        && !guild_players.iter().any(|p| changed.contains(p))
+                    {
+                        continue;
+                    }
+
+                    let report = leaderboard(guild_players, &mmrs);
+                    // Nobody's MMR is known yet, usually from API errors, so there's nothing to show
+                    if report.entries.is_empty() {
+                        continue;
+                    }
+
+                    let sent = match output.notifier(&notifiers) {
+                        Ok(n) => n.send_leaderboard(&report).await,
+                        Err(e) => Err(e),
+                    };
+
+                    match sent {
+                        Ok(()) => {
+                            info!(%output, "Updated MMR message");
+                            posted.insert(output);
+                        }
+                        Err(e) => {
+                            error!(%output, "Failed to update MMR message -> {e:#}");
+                            posted.remove(&output);
+                            METRICS.send_failures.with_label_values(&["mmr"]).inc();
+                        }
+                    }
+                }
+
+                changed.clear();
+            }
+            Ok(Event::Stopped) | Err(RecvError::Closed) => break,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(skipped, "Leaderboard fell behind, some events were skipped");
+            }
+            Ok(_) => {}
+        }
+    }
+
+    info!("MMR leaderboard stopped.");
+}
+
+/// A guild's players with a known MMR, highest first.
+fn leaderboard(players: &[PlayerData], mmrs: &HashMap<PlayerData, MmrDatum>) -> LeaderboardReport {
+    let mut sorted = players
+        .iter()
+        .filter_map(|p| mmrs.get(p).map(|d| (p, d)))
+        .collect::<Vec<(&PlayerData, &MmrDatum)>>();
+    sorted.sort_by_key(|(_, d)| Reverse(d.elo));
+
+    LeaderboardReport {
+        entries: sorted
+            .into_iter()
+            .map(|(player, data)| LeaderboardEntry {
+                player: ReportPlayer::from(player),
+                rank: data.current_tier_patched.clone(),
+                ranking_in_tier: data.ranking_in_tier,
+                elo: data.elo,
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,21 @@
+use tracing_subscriber::{fmt, EnvFilter};
+
+use crate::config::Config;
+
+/// Sets up the global logger, the filter and format were already checked when the config loaded.
+pub fn init(config: &Config) {
+    let builder = fmt()
+        .with_env_filter(EnvFilter::new(&config.log_filter))
+        .with_target(false);
+
+    if config.log_format == "json" {
+        // Span fields (player, match_id...) end up on every line logged inside them
+        builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .init();
+    } else {
+        builder.init();
+    }
+}
@@ -1,114 +1,393 @@
 use std::{
     fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
     process::exit,
+    sync::Arc,
 };
 
-use clap::Parser;
-use serenity::{prelude::GatewayIntents, Client};
-use tokio::{fs::File, io::AsyncReadExt, main, task};
+use serde_derive::{Deserialize, Serialize};
+use serenity::{http::Http, prelude::GatewayIntents, Client};
+use tokio::{
+    fs::File,
+    io::AsyncReadExt,
+    main, select,
+    sync::{Mutex, RwLock},
+    task,
+};
+use tracing::{error, info, warn};
 
 use crate::{
-    game_tracker::game_tracker_thread,
+    api::Api,
+    commands::Handler,
+    config::Config,
+    events::{Event, EventBus},
+    game_log::game_log_thread,
+    guilds::Guilds,
+    health::HEALTH,
     hendrix_matches_response::{HendrixMatchesResponse, MatchDatum, Player, TeamEnum},
     hendrix_mmr_response::{HendrixMmrResponse, MmrDatum},
-    mmr_tracker::mmr_tracker_thread,
+    leaderboard::leaderboard_thread,
+    notifier::NotifierContext,
+    players_file::{parse_players, watch_players_file},
+    poller::poller_thread,
+    shutdown::{wait_for_signal, Shutdown},
+    store::Store,
+    supervisor::supervise,
 };
 
-mod game_tracker;
+mod agents;
+mod api;
+mod cache;
+mod commands;
+mod config;
+mod events;
+mod game_log;
+mod guilds;
+mod head_to_head;
+mod health;
 mod hendrix_matches_response;
 mod hendrix_mmr_response;
-mod mmr_tracker;
-
-pub const BASE_URL: &str = "https://api.henrikdev.xyz";
-pub const MATCH_URL: &str = "/valorant/v3/matches/na";
-pub const MMR_HISTORY_URL: &str = "/valorant/v1/mmr-history/na";
-
-const PLAYER_FILE: &str = "./players.txt";
-
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
-struct Args {
-    /// Discord Bot Token
-    #[arg(short, long)]
-    token: String,
-
-    /// Game Log Channel ID
-    #[arg(short, long)]
-    game_channel: Option<u64>,
-
-    /// MMR Status Channel ID
-    #[arg(short, long)]
-    mmr_channel: Option<u64>,
-}
+mod leaderboard;
+mod logging;
+mod match_ranking;
+mod metrics;
+#[cfg(test)]
+mod mock_henrik;
+mod notifier;
+mod output;
+mod party;
+mod players_file;
+mod poller;
+mod records;
+mod replay;
+mod report;
+mod round_analysis;
+mod session;
+mod shutdown;
+mod status_server;
+mod store;
+mod streak;
+mod supervisor;
+#[cfg(test)]
+mod test_bot;
+#[cfg(test)]
+mod test_match;
 
 #[main]
 async fn main() {
-    let args = Args::parse();
+    let config = match Config::load() {
+        Ok(c) => Arc::new(c),
+        Err(e) => {
+            println!("ERROR: Invalid configuration -> {e:#}");
+            exit(1);
+        }
+    };
 
-    let players = match File::open(PLAYER_FILE).await {
+    logging::init(&config);
+
+    let player_file = config.player_file.display().to_string();
+    let mut guilds = Guilds::load().await;
+
+    let players = match File::open(&config.player_file).await {
         Ok(mut f) => {
             let mut players = String::new();
-            f.read_to_string(&mut players).await.unwrap();
-
-            players
-                .split('\n')
-                .map(|p| p.trim())
-                .map(|p| {
-                    let s = p.split('#').collect::<Vec<&str>>();
-                    if s.len() != 2 {
-                        panic!("Invalid player tag '{p}'!");
-                    }
+            if let Err(e) = f.read_to_string(&mut players).await {
+                error!(file = %player_file, "Failed to read the players file, is it saved as UTF-8? -> {e}");
+                exit(1);
+            }
 
-                    PlayerData {
-                        name: s[0].to_string(),
-                        tag: s[1].to_string(),
+            match parse_players(&players) {
+                Ok(p) => p,
+                Err(errors) => {
+                    for e in errors {
+                        error!(file = %player_file, "Invalid players file, {e}");
                     }
-                })
-                .collect::<Vec<PlayerData>>()
+
+                    exit(1);
+                }
+            }
         }
+        // Guilds set up through commands don't need the players file
+        Err(_) if !guilds.guilds.is_empty() => vec![],
         Err(_) => {
-            println!("The player file doesn't exist, creating...");
-            File::create(PLAYER_FILE).await.unwrap();
-            println!("Created {PLAYER_FILE}, please add all player tags (PlayerName#Tag) with a new line for each.");
+            warn!(file = %player_file, "The player file doesn't exist, creating...");
+            File::create(&config.player_file).await.unwrap();
+            info!("Created {player_file}, please add all player tags (PlayerName#Tag) with a new line for each.");
 
             exit(0);
         }
     };
 
-    if players.is_empty() {
-        panic!("Players file was empty! No players loaded!");
+    if players.is_empty() && guilds.guilds.is_empty() {
+        error!(file = %player_file, "No players to track, add a player tag (PlayerName#Tag) on each line.");
+        exit(1);
     }
 
-    println!("Loaded {} players.", players.len());
+    info!(
+        players = players.len(),
+        guilds = guilds.guilds.len(),
+        "Loaded players and guilds"
+    );
 
-    let mut client = Client::builder(args.token, GatewayIntents::default())
-        .await
-        .unwrap();
+    if !players.is_empty() {
+        guilds.default = Some(config.default_guild(players));
+    }
 
-    let ctx = client.cache_and_http.clone();
+    let guilds = Arc::new(RwLock::new(guilds));
 
-    if let Some(game_channel) = args.game_channel {
-        task::spawn(game_tracker_thread(
-            players.clone(),
-            ctx.clone(),
-            game_channel,
-        ));
+    task::spawn(watch_players_file(guilds.clone(), config.clone()));
 
-        println!("Spawned game tracker task!")
-    }
+    // A replay starts from nothing so it plays out the same every time, and mustn't overwrite the real state
+    let mut store = match config.replay_dir {
+        Some(_) => Store::default(),
+        None => Store::load().await,
+    };
+    store.read_only = config.dry_run || config.replay_dir.is_some();
+    let store = Arc::new(Mutex::new(store));
 
-    if let Some(mmr_channel) = args.mmr_channel {
-        task::spawn(mmr_tracker_thread(players, ctx, mmr_channel));
-        println!("Spawned mmr tracker task!")
+    let api = match Api::new(&config) {
+        Ok(api) => api,
+        Err(e) => {
+            error!("{e:#}");
+            exit(1);
+        }
+    };
+
+    let mut client = match &config.token {
+        Some(token) if !config.dry_run => Some(
+            Client::builder(token, GatewayIntents::default())
+                .event_handler(Handler {
+                    store: store.clone(),
+                    guilds: guilds.clone(),
+                })
+                .await
+                .unwrap(),
+        ),
+        _ => None,
+    };
+
+    let http = match &client {
+        Some(c) => c.cache_and_http.http.clone(),
+        // Webhooks don't need authentication, so an empty token is enough to post to them
+        None => {
+            if config.dry_run {
+                info!("Dry run, printing every post instead of sending it.");
+            } else {
+                info!("No Discord token given, only posting to webhooks and other outputs.");
+            }
+
+            HEALTH.without_gateway();
+            Arc::new(Http::new(""))
+        }
+    };
+
+    let notifiers = NotifierContext {
+        http: http.clone(),
+        client: reqwest::Client::new(),
+        store: store.clone(),
+        dry_run: config.dry_run,
+    };
+    let dry_run = config.dry_run;
+
+    let (shutdown_sender, shutdown) = Shutdown::new();
+
+    if let Some(address) = config.status_address {
+        task::spawn(status_server::serve(
+            address,
+            config.stale_after(),
+            shutdown.clone(),
+        ));
     }
 
-    client.start().await.expect("ERROR: Client failed to start");
+    let events = EventBus::new();
+
+    let game_log = task::spawn({
+        let (guilds, http, events, notifiers, store, config, shutdown) = (
+            guilds.clone(),
+            http.clone(),
+            events.subscription(),
+            notifiers.clone(),
+            store.clone(),
+            config.clone(),
+            shutdown.clone(),
+        );
+
+        async move {
+            supervise(
+                game_log::NAME,
+                &http,
+                config.admin_channel,
+                shutdown.clone(),
+                || {
+                    game_log_thread(
+                        guilds.clone(),
+                        events.receiver(),
+                        notifiers.clone(),
+                        store.clone(),
+                        config.clone(),
+                    )
+                },
+            )
+            .await
+        }
+    });
+    info!("Spawned game log task!");
+
+    let leaderboard = task::spawn({
+        let (guilds, http, subscription, notifiers, config, shutdown) = (
+            guilds.clone(),
+            http.clone(),
+            events.subscription(),
+            notifiers.clone(),
+            config.clone(),
+            shutdown.clone(),
+        );
+
+        async move {
+            supervise(
+                leaderboard::NAME,
+                &http,
+                config.admin_channel,
+                shutdown.clone(),
+                || {
+                    leaderboard_thread(
+                        guilds.clone(),
+                        subscription.receiver(),
+                        subscription.mmrs(),
+                        notifiers.clone(),
+                    )
+                },
+            )
+            .await
+        }
+    });
+    info!("Spawned leaderboard task!");
+
+    // Only once the reporters are subscribed, so they don't miss the first poll
+    let poller = task::spawn({
+        let (guilds, http, events, store, api, config, shutdown) = (
+            guilds.clone(),
+            http.clone(),
+            events.clone(),
+            store.clone(),
+            api.for_tracker("poller"),
+            config.clone(),
+            shutdown.clone(),
+        );
+
+        async move {
+            supervise(
+                poller::NAME,
+                &http,
+                config.admin_channel,
+                shutdown.clone(),
+                || {
+                    poller_thread(
+                        guilds.clone(),
+                        events.clone(),
+                        store.clone(),
+                        api.clone(),
+                        config.clone(),
+                        shutdown.clone(),
+                    )
+                },
+            )
+            .await
+        }
+    });
+    info!("Spawned poller task!");
+
+    let shard_manager = client.as_ref().map(|c| c.shard_manager.clone());
+    let stopped = task::spawn(async move {
+        select! {
+            _ = wait_for_signal() => {}
+            _ = api.replay_finished() => info!("Replayed every recorded response."),
+        }
+        info!("Shutting down, waiting for the poller and reporters to finish up...");
+
+        let _ = shutdown_sender.send(true);
+        let _ = poller.await;
+        // Reporters catch up on whatever the poller found before stopping
+        events.emit(Event::Stopped);
+        let _ = game_log.await;
+        let _ = leaderboard.await;
+
+        store.lock().await.save().await;
+        // Can't have changed without slash commands, and the real bot might be running alongside
+        if !dry_run {
+            guilds.read().await.save().await;
+        }
+
+        if let Some(shard_manager) = shard_manager {
+            shard_manager.lock().await.shutdown_all().await;
+        }
+    });
+
+    match &mut client {
+        Some(client) => client.start().await.expect("ERROR: Client failed to start"),
+        None => {
+            let _ = stopped.await;
+        }
+    }
+    info!("Shut down cleanly.");
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+/// A tracked player, only the name and tag identify them, the rest are settings from the players file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerData {
     pub name: String,
     pub tag: String,
+    /// Shown instead of the name in posts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+    /// Overrides the configured region for this player
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Discord user to mention in this player's posts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discord_user: Option<u64>,
+}
+
+impl PlayerData {
+    /// Parses a `Name#Tag` player tag.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let (name, tag) = tag.trim().split_once('#')?;
+        if name.is_empty() || tag.is_empty() || tag.contains('#') {
+            return None;
+        }
+
+        Some(Self {
+            name: name.to_string(),
+            tag: tag.to_string(),
+            nickname: None,
+            region: None,
+            discord_user: None,
+        })
+    }
+
+    /// Riot IDs are case insensitive, so `cooper#na123` is the same player as `Cooper#NA123`.
+    pub fn is(&self, name: &str, tag: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name) && self.tag.eq_ignore_ascii_case(tag)
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.nickname.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl PartialEq for PlayerData {
+    fn eq(&self, other: &Self) -> bool {
+        self.is(&other.name, &other.tag)
+    }
+}
+
+impl Eq for PlayerData {}
+
+impl Hash for PlayerData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.to_ascii_lowercase().hash(state);
+        self.tag.to_ascii_lowercase().hash(state);
+    }
 }
 
 impl Display for PlayerData {
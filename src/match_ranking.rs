@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+
+use crate::{MatchDatum, Player};
+
+/// Every player in a match ordered by ACS, with their place in the lobby and on their team.
+pub struct MatchRanking {
+    pub players: Vec<RankedPlayer>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RankedPlayer {
+    pub puuid: String,
+    pub acs: f64,
+    /// 1 is the best
+    pub lobby_position: usize,
+    /// 1 is the best
+    pub team_position: usize,
+}
+
+impl RankedPlayer {
+    pub fn is_match_mvp(&self) -> bool {
+        self.lobby_position == 1
+    }
+
+    pub fn is_team_mvp(&self) -> bool {
+        self.team_position == 1
+    }
+
+    pub fn badge(&self) -> Option<&'static str> {
+        if self.is_match_mvp() {
+            Some("⭐ **Match MVP**")
+        } else if self.is_team_mvp() {
+            Some("🌟 **Team MVP**")
+        } else {
+            None
+        }
+    }
+}
+
+impl MatchRanking {
+    pub fn of(game: &MatchDatum) -> Self {
+        let rounds = game.rounds.len().max(1) as f64;
+
+        let mut sorted = game.players.all_players.iter().collect::<Vec<&Player>>();
+        sorted.sort_by(|a, b| compare(a, b, rounds));
+
+        let players = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let team_position = sorted
+                    .iter()
+                    .filter(|t| t.team == p.team)
+                    .position(|t| t.puuid == p.puuid)
+                    .unwrap_or(0)
+                    + 1;
+
+                RankedPlayer {
+                    puuid: p.puuid.clone(),
+                    acs: acs(p, rounds),
+                    lobby_position: i + 1,
+                    team_position,
+                }
+            })
+            .collect();
+
+        Self { players }
+    }
+
+    pub fn get(&self, puuid: &str) -> Option<&RankedPlayer> {
+        self.players.iter().find(|p| p.puuid == puuid)
+    }
+}
+
+fn acs(player: &Player, rounds: f64) -> f64 {
+    player.stats.score as f64 / rounds
+}
+
+/// Higher ACS first, then more kills, fewer deaths, more assists, and finally puuid so ties never depend on API order.
+fn compare(a: &Player, b: &Player, rounds: f64) -> Ordering {
+    acs(b, rounds)
+        .total_cmp(&acs(a, rounds))
+        .then_with(|| b.stats.kills.cmp(&a.stats.kills))
+        .then_with(|| a.stats.deaths.cmp(&b.stats.deaths))
+        .then_with(|| b.stats.assists.cmp(&a.stats.assists))
+        .then_with(|| a.puuid.cmp(&b.puuid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_match::TestMatch;
+
+    fn positions(game: &MatchDatum) -> Vec<(String, usize, usize, Option<&'static str>)> {
+        let ranking = MatchRanking::of(game);
+        ranking
+            .players
+            .iter()
+            .map(|p| {
+                (
+                    p.puuid.clone(),
+                    p.lobby_position,
+                    p.team_position,
+                    p.badge(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ranks_by_acs_then_breaks_ties() {
+        // (puuid, team, [score, kills, deaths, assists])
+        let players = [
+            ("d", "Red", [3000, 12, 10, 4]),
+            ("a", "Blue", [4000, 20, 8, 2]),
+            // Same ACS as d, more kills
+            ("c", "Red", [3000, 13, 12, 1]),
+            // Same as d in everything, so it comes down to the puuid
+            ("b", "Blue", [3000, 12, 10, 4]),
+            ("e", "Red", [1000, 3, 15, 6]),
+        ];
+
+        let game = players
+            .iter()
+            .fold(TestMatch::new().rounds(10), |m, (puuid, team, stats)| {
+                m.player(puuid, team, *stats)
+            })
+            .build();
+
+        let mvp = Some("⭐ **Match MVP**");
+        let team_mvp = Some("🌟 **Team MVP**");
+        let expected = [
+            ("a", 1, 1, mvp),
+            ("c", 2, 1, team_mvp),
+            ("b", 3, 2, None),
+            ("d", 4, 2, None),
+            ("e", 5, 3, None),
+        ];
+
+        assert_eq!(
+            positions(&game),
+            expected.map(|(p, l, t, b)| (p.to_string(), l, t, b))
+        );
+        assert_eq!(MatchRanking::of(&game).get("a").unwrap().acs, 400.0);
+    }
+
+    #[test]
+    fn zero_round_match_doesnt_divide_by_zero() {
+        let game = TestMatch::new()
+            .player("a", "Red", [0, 0, 0, 0])
+            .player("b", "Blue", [150, 1, 0, 0])
+            .build();
+
+        let ranking = MatchRanking::of(&game);
+        assert_eq!(ranking.get("b").unwrap().acs, 150.0);
+        assert_eq!(ranking.get("b").unwrap().lobby_position, 1);
+        assert!(ranking.players.iter().all(|p| p.acs.is_finite()));
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::Instant,
+};
+
+use prometheus::{
+    histogram_opts, opts, Encoder, GaugeVec, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec,
+    Registry, TextEncoder,
+};
+
+use crate::PlayerData;
+
+/// Every metric the bot exposes, they're global so any module can record without threading them through.
+pub struct Metrics {
+    registry: Registry,
+    pub polls: IntCounterVec,
+    pub api_latency: HistogramVec,
+    pub api_responses: IntCounterVec,
+    pub api_decode_failures: IntCounterVec,
+    pub api_cache_hits: IntCounterVec,
+    pub send_failures: IntCounterVec,
+    pub matches_posted: IntCounter,
+    pub players_tracked: IntGaugeVec,
+    since_last_poll: GaugeVec,
+    /// Turned into `since_last_poll` when scraped, keyed by tracker and player
+    last_polls: Mutex<HashMap<(&'static str, String), Instant>>,
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("valorant_tracker".to_string()), None).unwrap();
+
+        let polls = IntCounterVec::new(
+            opts!("polls_total", "Polling rounds completed by the poller"),
+            &["tracker"],
+        )
+        .unwrap();
+        let api_latency = HistogramVec::new(
+            histogram_opts!(
+                "api_request_duration_seconds",
+                "HenrikDev request latency",
+                vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+        let api_responses = IntCounterVec::new(
+            opts!(
+                "api_responses_total",
+                "HenrikDev responses by status code, 'error' if the request never got a response"
+            ),
+            &["endpoint", "status"],
+        )
+        .unwrap();
+        let api_decode_failures = IntCounterVec::new(
+            opts!(
+                "api_decode_failures_total",
+                "HenrikDev responses that couldn't be decoded"
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+        let api_cache_hits = IntCounterVec::new(
+            opts!(
+                "api_cache_hits_total",
+                "HenrikDev requests answered from the response cache instead of a new request"
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+        let send_failures = IntCounterVec::new(
+            opts!(
+                "send_failures_total",
+                "Reports that failed to send to an output, like Discord"
+            ),
+            &["kind"],
+        )
+        .unwrap();
+        let matches_posted = IntCounter::new(
+            "matches_posted_total",
+            "Match messages sent, once per channel",
+        )
+        .unwrap();
+        let players_tracked = IntGaugeVec::new(
+            opts!("players_tracked", "Unique players polled by each tracker"),
+            &["tracker"],
+        )
+        .unwrap();
+        let since_last_poll = GaugeVec::new(
+            opts!(
+                "seconds_since_successful_poll",
+                "Seconds since a player was last polled successfully"
+            ),
+            &["tracker", "player"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(polls.clone())).unwrap();
+        registry.register(Box::new(api_latency.clone())).unwrap();
+        registry.register(Box::new(api_responses.clone())).unwrap();
+        registry
+            .register(Box::new(api_decode_failures.clone()))
+            .unwrap();
+        registry.register(Box::new(api_cache_hits.clone())).unwrap();
+        registry.register(Box::new(send_failures.clone())).unwrap();
+        registry.register(Box::new(matches_posted.clone())).unwrap();
+        registry
+            .register(Box::new(players_tracked.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(since_last_poll.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            polls,
+            api_latency,
+            api_responses,
+            api_decode_failures,
+            api_cache_hits,
+            send_failures,
+            matches_posted,
+            players_tracked,
+            since_last_poll,
+            last_polls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn poll_succeeded(&self, tracker: &'static str, player: &PlayerData) {
+        self.last_polls
+            .lock()
+            .unwrap()
+            .insert((tracker, player.to_string()), Instant::now());
+    }
+
+    /// Updates the tracked player count, and forgets players that aren't tracked anymore.
+    pub fn set_players(&self, tracker: &'static str, players: &[PlayerData]) {
+        self.players_tracked
+            .with_label_values(&[tracker])
+            .set(players.len() as i64);
+
+        let names = players.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        self.last_polls
+            .lock()
+            .unwrap()
+            .retain(|(t, p), _| *t != tracker || names.contains(p));
+    }
+
+    /// Renders every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        self.since_last_poll.reset();
+        for ((tracker, player), at) in self.last_polls.lock().unwrap().iter() {
+            self.since_last_poll
+                .with_label_values(&[tracker, player])
+                .set(at.elapsed().as_secs_f64());
+        }
+
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use tokio::{sync::oneshot, task, time};
+
+use crate::api::{MATCH_URL, MMR_HISTORY_URL};
+
+/// A local stand-in for HenrikDev, serving canned responses so polling and posting can be tested end to end.
+///
+/// Responses are queued per endpoint and player, and the last one keeps being served once the rest are used up,
+/// like the real API keeps returning someone's latest match.
+pub struct MockHenrik {
+    pub base_url: String,
+    routes: Arc<Mutex<HashMap<(String, String), Route>>>,
+    stop: Option<oneshot::Sender<()>>,
+}
+
+#[derive(Default)]
+struct Route {
+    responses: VecDeque<(StatusCode, String)>,
+    hits: usize,
+}
+
+impl MockHenrik {
+    pub async fn start() -> Self {
+        let routes = Arc::new(Mutex::new(HashMap::<(String, String), Route>::new()));
+        let (stop, stopped) = oneshot::channel::<()>();
+
+        let make_service = make_service_fn({
+            let routes = routes.clone();
+            move |_| {
+                let routes = routes.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |r| {
+                        let response = respond(&routes, r);
+                        async move { Ok::<_, Infallible>(response) }
+                    }))
+                }
+            }
+        });
+
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let base_url = format!("http://{}", server.local_addr());
+        task::spawn(server.with_graceful_shutdown(async {
+            let _ = stopped.await;
+        }));
+
+        Self {
+            base_url,
+            routes,
+            stop: Some(stop),
+        }
+    }
+
+    /// Queues a response for `endpoint` (matches or mmr_history) and `Name#Tag`.
+    pub fn respond(&self, endpoint: &str, player: &str, status: u16, body: &str) {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry((endpoint.to_string(), player.to_string()))
+            .or_default()
+            .responses
+            .push_back((StatusCode::from_u16(status).unwrap(), body.to_string()));
+    }
+
+    /// Queues every response in a `--record-dir` recording, in the order they were received.
+    pub fn respond_with_recording(&self, dir: &Path) {
+        let mut files = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect::<Vec<_>>();
+        // Sorted by when they were received, then by sequence number
+        files.sort_by_key(|p| {
+            let name = p.file_name().unwrap().to_str().unwrap().to_string();
+            let mut parts = name.splitn(3, '-');
+            let received_at = parts.next().unwrap().parse::<i64>().unwrap();
+            let seq = parts.next().unwrap().parse::<u64>().unwrap();
+            (received_at, seq)
+        });
+
+        for path in files {
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            let [_, _, _, endpoint, player] = name.splitn(5, '-').collect::<Vec<_>>()[..] else {
+                panic!("{} isn't a recorded response", path.display());
+            };
+
+            self.respond(
+                endpoint,
+                player,
+                200,
+                &std::fs::read_to_string(&path).unwrap(),
+            );
+        }
+    }
+
+    /// How many times `endpoint` was requested for `Name#Tag`.
+    pub fn hits(&self, endpoint: &str, player: &str) -> usize {
+        self.routes
+            .lock()
+            .unwrap()
+            .get(&(endpoint.to_string(), player.to_string()))
+            .map(|r| r.hits)
+            .unwrap_or_default()
+    }
+
+    /// Waits for `count` more requests to `endpoint` for `Name#Tag`, a few minutes of polling at most.
+    ///
+    /// Paused time jumps ahead while a request is in flight, so sleeping a fixed time doesn't guarantee any polls.
+    pub async fn wait_for_hits(&self, endpoint: &str, player: &str, count: usize) {
+        let target = self.hits(endpoint, player) + count;
+        let polled = async {
+            while self.hits(endpoint, player) < target {
+                time::sleep(Duration::from_secs(1)).await;
+            }
+        };
+
+        time::timeout(Duration::from_secs(300), polled)
+            .await
+            .unwrap_or_else(|_| panic!("{player} was never polled {count} more times"));
+    }
+}
+
+impl Drop for MockHenrik {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
+
+fn respond(
+    routes: &Mutex<HashMap<(String, String), Route>>,
+    request: Request<Body>,
+) -> Response<Body> {
+    let path = request.uri().path().replace("%20", " ");
+
+    // `{endpoint url}/{region}/{name}/{tag}`, the region doesn't matter here
+    let route = [("matches", MATCH_URL), ("mmr_history", MMR_HISTORY_URL)]
+        .into_iter()
+        .find_map(|(endpoint, url)| {
+            let rest = path.strip_prefix(url)?.strip_prefix('/')?;
+            let [_, name, tag] = rest.split('/').collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            Some((endpoint.to_string(), format!("{name}#{tag}")))
+        });
+
+    let mut routes = routes.lock().unwrap();
+    let (status, body) = match route.and_then(|r| routes.get_mut(&r)) {
+        Some(route) => {
+            route.hits += 1;
+            if route.responses.len() > 1 {
+                route.responses.pop_front().unwrap()
+            } else {
+                route.responses.front().cloned().unwrap()
+            }
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            r#"{"status":404,"errors":[{"message":"Not found"}]}"#.to_string(),
+        ),
+    };
+
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_bot::Bot;
+
+    const ZERO_SHOTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/replay/zero_shots");
+
+    /// The fixtures are from long ago, so Zero#SHOT is only polled every `max_poll_interval` seconds.
+    async fn start_bot(henrik: &MockHenrik, name: &str, max_poll_interval: u64) -> Bot {
+        Bot::start(
+            name,
+            &format!(
+                "base_url = {:?}\nmax_poll_interval = {max_poll_interval}",
+                henrik.base_url
+            ),
+            &["Zero#SHOT"],
+        )
+        .await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn posts_new_matches_and_the_leaderboard_once() {
+        let henrik = MockHenrik::start().await;
+        henrik.respond_with_recording(Path::new(ZERO_SHOTS));
+        let bot = start_bot(&henrik, "posts_once", 5).await;
+
+        let games = bot.wait_for("game", 1).await;
+        let boards = bot
+            .wait_until("mmr", |b| b["entries"][0]["elo"] == 941)
+            .await;
+
+        // The match from before starting is only remembered, the new one is posted
+        assert_eq!(games[0]["type"], "match");
+        assert_eq!(games[0]["match_id"], "c3d9e2f1-7a6b-4c5d-8e9f-0a1b2c3d4e5f");
+        assert_eq!(games[0]["player"]["tag"], "Zero#SHOT");
+
+        // Posted on the first poll, then updated with the MMR from the new match
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[0]["type"], "leaderboard");
+        assert_eq!(boards[0]["entries"][0]["elo"], 958);
+        assert_eq!(boards[1]["entries"][0]["rank"], "Gold 1");
+        assert_eq!(boards[1]["entries"][0]["elo"], 941);
+
+        // Nothing changes after that, so a few more polls shouldn't post anything
+        henrik.wait_for_hits("matches", "Zero#SHOT", 3).await;
+
+        let (games, boards) = bot.stop().await;
+        assert_eq!(games.len(), 1);
+        assert_eq!(boards.len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keeps_polling_through_api_errors() {
+        let henrik = MockHenrik::start().await;
+        let rate_limited = r#"{"status":429,"errors":[{"message":"Rate limit reached"}]}"#;
+        henrik.respond("matches", "Zero#SHOT", 429, rate_limited);
+        henrik.respond("matches", "Zero#SHOT", 200, "<html>Bad Gateway</html>");
+        henrik.respond("mmr_history", "Zero#SHOT", 429, rate_limited);
+        henrik.respond_with_recording(Path::new(ZERO_SHOTS));
+        let bot = start_bot(&henrik, "api_errors", 5).await;
+
+        let games = bot.wait_for("game", 1).await;
+        bot.wait_for("mmr", 1).await;
+
+        assert_eq!(games[0]["match_id"], "c3d9e2f1-7a6b-4c5d-8e9f-0a1b2c3d4e5f");
+        assert!(henrik.hits("matches", "Zero#SHOT") >= 4);
+
+        // The first polls failed, which left nothing to put on the board
+        let (_, boards) = bot.stop().await;
+        assert!(boards.iter().all(|b| b["entries"][0]["elo"].is_number()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn restarted_leaderboard_posts_every_known_mmr() {
+        let henrik = MockHenrik::start().await;
+        henrik.respond_with_recording(Path::new(ZERO_SHOTS));
+        let mut bot = start_bot(&henrik, "leaderboard_restart", 5).await;
+        bot.wait_until("mmr", |b| b["entries"][0]["elo"] == 941)
+            .await;
+
+        // Nobody's elo changes after this, so the new one only knows what it was started with
+        bot.restart_leaderboard().await;
+        let boards = bot.wait_for("mmr", 3).await;
+        assert_eq!(boards[2]["entries"][0]["rank"], "Gold 1");
+        assert_eq!(boards[2]["entries"][0]["elo"], 941);
+
+        henrik.wait_for_hits("matches", "Zero#SHOT", 3).await;
+        let (_, boards) = bot.stop().await;
+        assert_eq!(boards.len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn backs_off_players_who_havent_played_in_a_while() {
+        let henrik = MockHenrik::start().await;
+        henrik.respond_with_recording(Path::new(ZERO_SHOTS));
+        let bot = start_bot(&henrik, "backs_off", 60).await;
+        bot.wait_for("mmr", 1).await;
+
+        // Every 5 seconds would be 24 polls, waiting the max of a minute is only 2
+        let polled = henrik.hits("matches", "Zero#SHOT");
+        time::sleep(Duration::from_secs(120)).await;
+        assert!(henrik.hits("matches", "Zero#SHOT") - polled <= 3);
+
+        bot.stop().await;
+    }
+}
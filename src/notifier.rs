@@ -0,0 +1,381 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde_derive::Serialize;
+use serde_json::Value;
+use serenity::{
+    async_trait,
+    builder::CreateEmbed,
+    http::Http,
+    json::{hashmap_to_json_map, JsonMap},
+    model::{
+        id::{ChannelId, MessageId},
+        Timestamp,
+    },
+    utils::Color,
+};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+use crate::{
+    report::{Card, LeaderboardReport, MatchReport, SessionReport},
+    store::SharedStore,
+};
+
+/// Somewhere reports get sent, made from an `Output` whenever one is needed.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send_match(&self, report: &MatchReport) -> Result<()>;
+
+    async fn send_session(&self, report: &SessionReport) -> Result<()>;
+
+    /// Replaces the previous leaderboard where the sink can, otherwise adds a new one.
+    async fn send_leaderboard(&self, report: &LeaderboardReport) -> Result<()>;
+}
+
+/// Everything notifiers share, cheap to clone.
+#[derive(Clone)]
+pub struct NotifierContext {
+    pub http: Arc<Http>,
+    pub client: Client,
+    /// Keeps track of leaderboard messages to edit
+    pub store: SharedStore,
+    /// Sends everything to the console instead
+    pub dry_run: bool,
+}
+
+pub enum DiscordTarget {
+    Channel(ChannelId),
+    Webhook { id: u64, token: String },
+}
+
+/// Posts embeds to a channel through the bot, or to a webhook.
+pub struct DiscordNotifier {
+    http: Arc<Http>,
+    store: SharedStore,
+    target: DiscordTarget,
+}
+
+impl DiscordNotifier {
+    pub fn new(ctx: &NotifierContext, target: DiscordTarget) -> Self {
+        Self {
+            http: ctx.http.clone(),
+            store: ctx.store.clone(),
+            target,
+        }
+    }
+
+    /// Channel and webhook ids are both snowflakes, so they can share a key space.
+    fn id(&self) -> u64 {
+        match &self.target {
+            DiscordTarget::Channel(channel) => channel.0,
+            DiscordTarget::Webhook { id, .. } => *id,
+        }
+    }
+
+    /// Posts a message, the content is left out if empty.
+    async fn send(&self, content: &str, embed: Option<CreateEmbed>) -> Result<MessageId> {
+        match &self.target {
+            DiscordTarget::Channel(channel) => {
+                let message = channel
+                    .send_message(&self.http, |m| {
+                        if !content.is_empty() {
+                            m.content(content);
+                        }
+                        if let Some(embed) = embed {
+                            m.set_embed(embed);
+                        }
+                        m
+                    })
+                    .await?;
+
+                Ok(message.id)
+            }
+            DiscordTarget::Webhook { id, token } => {
+                let message = self
+                    .http
+                    .execute_webhook(*id, token, true, &webhook_body(content, embed))
+                    .await?
+                    .ok_or_else(|| anyhow!("webhook didn't return the message it sent"))?;
+
+                Ok(message.id)
+            }
+        }
+    }
+
+    async fn edit(&self, message: MessageId, content: &str) -> Result<()> {
+        match &self.target {
+            DiscordTarget::Channel(channel) => {
+                channel
+                    .edit_message(&self.http, message, |m| m.content(content))
+                    .await?;
+            }
+            DiscordTarget::Webhook { id, token } => {
+                self.http
+                    .edit_webhook_message(*id, token, message.0, &webhook_body(content, None))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The leaderboard message from before, or for channels the latest message like it's always been.
+    async fn leaderboard_message(&self) -> Option<MessageId> {
+        if let Some(message) = self.store.lock().await.leaderboard_messages.get(&self.id()) {
+            return Some(MessageId(*message));
+        }
+
+        match &self.target {
+            DiscordTarget::Channel(channel) => channel
+                .messages(&self.http, |b| b.limit(1))
+                .await
+                .ok()
+                .and_then(|m| m.first().map(|m| m.id)),
+            DiscordTarget::Webhook { .. } => None,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    async fn send_match(&self, report: &MatchReport) -> Result<()> {
+        let mention = report.player.mention();
+        self.send(&mention, Some(embed(&report.card))).await?;
+
+        if let Some(announcement) = &report.streak_announcement {
+            self.send(&format!("{announcement} {mention}"), None)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn send_session(&self, report: &SessionReport) -> Result<()> {
+        self.send("", Some(embed(&report.card))).await?;
+        Ok(())
+    }
+
+    async fn send_leaderboard(&self, report: &LeaderboardReport) -> Result<()> {
+        let content = report.render();
+
+        let message = match self.leaderboard_message().await {
+            Some(message) if self.edit(message, &content).await.is_ok() => message,
+            // Deleted, or someone else's message, so start a new one
+            _ => self.send(&content, None).await?,
+        };
+
+        self.store
+            .lock()
+            .await
+            .leaderboard_messages
+            .insert(self.id(), message.0);
+
+        Ok(())
+    }
+}
+
+/// Every report, tagged with its type so consumers can tell them apart.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Report<'a> {
+    Match(&'a MatchReport),
+    Session(&'a SessionReport),
+    Leaderboard(&'a LeaderboardReport),
+}
+
+/// POSTs reports as JSON to any URL.
+pub struct JsonWebhookNotifier {
+    client: Client,
+    url: String,
+}
+
+impl JsonWebhookNotifier {
+    pub fn new(ctx: &NotifierContext, url: &str) -> Self {
+        Self {
+            client: ctx.client.clone(),
+            url: url.to_string(),
+        }
+    }
+
+    async fn post(&self, report: Report<'_>) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(&report)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for JsonWebhookNotifier {
+    async fn send_match(&self, report: &MatchReport) -> Result<()> {
+        self.post(Report::Match(report)).await
+    }
+
+    async fn send_session(&self, report: &SessionReport) -> Result<()> {
+        self.post(Report::Session(report)).await
+    }
+
+    async fn send_leaderboard(&self, report: &LeaderboardReport) -> Result<()> {
+        self.post(Report::Leaderboard(report)).await
+    }
+}
+
+/// Appends reports to a file, one JSON object per line.
+pub struct JsonLinesNotifier {
+    path: PathBuf,
+}
+
+impl JsonLinesNotifier {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    async fn append(&self, report: Report<'_>) -> Result<()> {
+        let mut line = serde_json::to_vec(&report)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+
+        // Written in one go so lines from both reporters never interleave
+        file.write_all(&line).await?;
+        // Tokio writes in the background, so it might not have happened yet otherwise
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for JsonLinesNotifier {
+    async fn send_match(&self, report: &MatchReport) -> Result<()> {
+        self.append(Report::Match(report)).await
+    }
+
+    async fn send_session(&self, report: &SessionReport) -> Result<()> {
+        self.append(Report::Session(report)).await
+    }
+
+    async fn send_leaderboard(&self, report: &LeaderboardReport) -> Result<()> {
+        self.append(Report::Leaderboard(report)).await
+    }
+}
+
+/// Prints reports to the terminal, what `--dry-run` swaps every output for.
+pub struct ConsoleNotifier {
+    label: String,
+}
+
+impl ConsoleNotifier {
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+        }
+    }
+
+    fn print(&self, card: &Card, extra: &[String]) {
+        let mut lines = vec![
+            format!("── {} → {} ──", card.title, self.label),
+            match card.won {
+                Some(true) => "Won",
+                Some(false) => "Lost",
+                None => "Draw",
+            }
+            .to_string(),
+        ];
+
+        lines.extend(card.description.lines().map(|l| l.to_string()));
+        lines.extend(
+            card.fields
+                .iter()
+                .map(|f| format!("{}: {}", f.name, f.value)),
+        );
+        lines.extend(extra.iter().cloned());
+
+        // Built up front so both reporters printing at once don't interleave
+        println!("{}\n", lines.join("\n  "));
+    }
+}
+
+#[async_trait]
+impl Notifier for ConsoleNotifier {
+    async fn send_match(&self, report: &MatchReport) -> Result<()> {
+        let extra = report
+            .streak_announcement
+            .iter()
+            .map(|a| format!("{a} {}", report.player.mention()))
+            .collect::<Vec<String>>();
+
+        self.print(&report.card, &extra);
+        Ok(())
+    }
+
+    async fn send_session(&self, report: &SessionReport) -> Result<()> {
+        self.print(&report.card, &[]);
+        Ok(())
+    }
+
+    async fn send_leaderboard(&self, report: &LeaderboardReport) -> Result<()> {
+        println!(
+            "── MMR Leaderboard → {} ──\n  {}\n",
+            self.label,
+            report.render().replace('\n', "\n  ")
+        );
+        Ok(())
+    }
+}
+
+/// The Discord embed for a report's card.
+pub fn embed(card: &Card) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(&card.title)
+        .color(match card.won {
+            Some(true) => Color::DARK_GREEN,
+            Some(false) => Color::DARK_RED,
+            None => Color::DARK_GREY,
+        })
+        .timestamp(
+            Timestamp::from_unix_timestamp(card.timestamp).unwrap_or_else(|_| Timestamp::now()),
+        )
+        .fields(card.fields.iter().map(|f| (&f.name, &f.value, true)));
+
+    if !card.description.is_empty() {
+        embed.description(&card.description);
+    }
+
+    if let Some(image) = &card.image {
+        embed.image(image);
+    }
+
+    if let Some(thumbnail) = &card.thumbnail {
+        embed.thumbnail(thumbnail);
+    }
+
+    embed
+}
+
+fn webhook_body(content: &str, embed: Option<CreateEmbed>) -> JsonMap {
+    let mut body = JsonMap::new();
+    body.insert("content".to_string(), Value::from(content));
+
+    let embeds = embed
+        .map(|e| vec![Value::from(hashmap_to_json_map(e.0))])
+        .unwrap_or_default();
+    body.insert("embeds".to_string(), Value::from(embeds));
+
+    body
+}
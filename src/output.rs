@@ -0,0 +1,127 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::Url;
+use serde_derive::{Deserialize, Serialize};
+use serenity::{model::id::ChannelId, utils::parse_webhook};
+
+use crate::notifier::{
+    ConsoleNotifier, DiscordNotifier, DiscordTarget, JsonLinesNotifier, JsonWebhookNotifier,
+    Notifier, NotifierContext,
+};
+
+/// Which feed an output is for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Feed {
+    Game,
+    Mmr,
+}
+
+/// An extra output from the config, ex. `{ feed = "game", kind = "json_lines", path = "./games.jsonl" }`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedOutput {
+    pub feed: Feed,
+    #[serde(flatten)]
+    pub output: Output,
+}
+
+/// Where a feed is posted.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Output {
+    /// A channel, posted to through the bot
+    Channel { channel: u64 },
+    /// For servers the bot isn't in
+    DiscordWebhook { url: String },
+    /// POSTs every report as JSON, for our own tooling
+    JsonWebhook { url: String },
+    /// Appends every report as a line of JSON
+    JsonLines { path: PathBuf },
+    /// Prints every report to the terminal
+    Console,
+}
+
+impl Output {
+    pub fn channel(channel: u64) -> Self {
+        Self::Channel { channel }
+    }
+
+    pub fn discord_webhook(url: &str) -> Self {
+        Self::DiscordWebhook {
+            url: url.to_string(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::Channel { .. } | Self::JsonLines { .. } | Self::Console => {}
+            Self::DiscordWebhook { url } => {
+                discord_webhook(url)?;
+            }
+            Self::JsonWebhook { url } => {
+                let parsed = Url::parse(url).with_context(|| format!("invalid URL '{url}'"))?;
+                if !["http", "https"].contains(&parsed.scheme()) {
+                    bail!("JSON webhook URL '{url}' should start with http:// or https://");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn notifier(&self, ctx: &NotifierContext) -> Result<Box<dyn Notifier>> {
+        // Every output still gets its own label, so a dry run shows where things would have gone
+        if ctx.dry_run {
+            return Ok(Box::new(ConsoleNotifier::new(&self.to_string())));
+        }
+
+        Ok(match self {
+            Self::Channel { channel } => Box::new(DiscordNotifier::new(
+                ctx,
+                DiscordTarget::Channel(ChannelId(*channel)),
+            )),
+            Self::DiscordWebhook { url } => {
+                let (id, token) = discord_webhook(url)?;
+                Box::new(DiscordNotifier::new(
+                    ctx,
+                    DiscordTarget::Webhook { id, token },
+                ))
+            }
+            Self::JsonWebhook { url } => Box::new(JsonWebhookNotifier::new(ctx, url)),
+            Self::JsonLines { path } => Box::new(JsonLinesNotifier::new(path)),
+            Self::Console => Box::new(ConsoleNotifier::new("console")),
+        })
+    }
+}
+
+/// Parses a `https://discord.com/api/webhooks/<id>/<token>` webhook URL.
+fn discord_webhook(url: &str) -> Result<(u64, String)> {
+    let parsed = Url::parse(url).with_context(|| format!("invalid webhook URL '{url}'"))?;
+    let (id, token) =
+        parse_webhook(&parsed).ok_or_else(|| anyhow!("'{url}' isn't a Discord webhook URL"))?;
+
+    Ok((id, token.to_string()))
+}
+
+/// Never shows webhook URLs since they contain a token, so outputs are safe to log.
+impl Display for Output {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Channel { channel } => write!(f, "{channel}"),
+            Self::DiscordWebhook { url } => match discord_webhook(url) {
+                Ok((id, _)) => write!(f, "webhook {id}"),
+                Err(_) => write!(f, "webhook"),
+            },
+            Self::JsonWebhook { url } => match Url::parse(url) {
+                Ok(u) => write!(f, "JSON webhook {}", u.host_str().unwrap_or_default()),
+                Err(_) => write!(f, "JSON webhook"),
+            },
+            Self::JsonLines { path } => write!(f, "{}", path.display()),
+            Self::Console => write!(f, "console"),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{MatchDatum, Player, PlayerData};
+
+/// How a player performs depending on who they queued with.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct PartyStats {
+    /// Keyed by the full party size, including players we don't track
+    pub by_size: HashMap<usize, SynergyTotals>,
+    /// Keyed by the sorted tracked teammates in the party, empty when nobody tracked was in it
+    pub by_teammates: HashMap<String, SynergyTotals>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct SynergyTotals {
+    pub games: i64,
+    pub wins: i64,
+    /// Only games where we caught the MMR change count towards RR
+    pub rr_games: i64,
+    pub rr: i64,
+    pub acs: f64,
+}
+
+impl SynergyTotals {
+    fn add(&mut self, won: bool, rr: Option<i64>, acs: f64) {
+        self.games += 1;
+        self.acs += acs;
+
+        if won {
+            self.wins += 1;
+        }
+
+        if let Some(rr) = rr {
+            self.rr_games += 1;
+            self.rr += rr;
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let games = self.games.max(1) as f64;
+        let mut summary = format!(
+            "{} games, {:.0}% WR, {:.0} ACS",
+            self.games,
+            self.wins as f64 / games * 100_f64,
+            self.acs / games
+        );
+
+        if self.rr_games > 0 {
+            summary = format!(
+                "{summary}, {:+.1} RR avg",
+                self.rr as f64 / self.rr_games as f64
+            );
+        }
+
+        summary
+    }
+}
+
+/// The party a player queued with in a single match.
+pub struct Party {
+    pub size: usize,
+    pub tracked_teammates: Vec<String>,
+}
+
+impl Party {
+    pub fn of(game: &MatchDatum, player: &Player, tracked: &[PlayerData]) -> Self {
+        let members = match &player.party_id {
+            Some(party_id) => game
+                .players
+                .all_players
+                .iter()
+                .filter(|p| p.party_id.as_ref() == Some(party_id) && p.puuid != player.puuid)
+                .collect::<Vec<&Player>>(),
+            None => vec![],
+        };
+
+        let mut tracked_teammates = members
+            .iter()
+            .filter(|m| tracked.iter().any(|t| t.is(&m.name, &m.tag)))
+            .map(|m| format!("{}#{}", m.name, m.tag))
+            .collect::<Vec<String>>();
+        tracked_teammates.sort();
+
+        Self {
+            size: members.len() + 1,
+            tracked_teammates,
+        }
+    }
+}
+
+impl PartyStats {
+    pub fn record(&mut self, party: &Party, won: bool, rr: Option<i64>, acs: f64) {
+        self.by_size
+            .entry(party.size)
+            .or_default()
+            .add(won, rr, acs);
+        self.by_teammates
+            .entry(party.tracked_teammates.join(", "))
+            .or_default()
+            .add(won, rr, acs);
+    }
+}
+
+pub fn party_size_name(size: usize) -> String {
+    match size {
+        1 => "Solo".to_string(),
+        2 => "Duo".to_string(),
+        3 => "Trio".to_string(),
+        5 => "Full Stack".to_string(),
+        n => format!("{n}-Stack"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_match::TestMatch;
+
+    fn game() -> MatchDatum {
+        TestMatch::new()
+            .player("me", "Red", [0, 0, 0, 0])
+            .player("joe", "Red", [0, 0, 0, 0])
+            .player("random", "Red", [0, 0, 0, 0])
+            .player("solo", "Red", [0, 0, 0, 0])
+            .player("enemy", "Blue", [0, 0, 0, 0])
+            .named("me", "Cooper", "NA123")
+            .named("joe", "Joe", "EU631")
+            .named("random", "Random", "0000")
+            .named("solo", "Mom", "OKAY33")
+            .named("enemy", "Enemy", "1111")
+            .party("stack", &["me", "joe", "random"])
+            .party("other", &["enemy"])
+            .build()
+    }
+
+    fn player<'a>(game: &'a MatchDatum, puuid: &str) -> &'a Player {
+        game.players
+            .all_players
+            .iter()
+            .find(|p| p.puuid == puuid)
+            .unwrap()
+    }
+
+    #[test]
+    fn finds_the_party_and_tracked_teammates() {
+        let game = game();
+        let tracked =
+            ["cooper#na123", "JOE#EU631", "Mom#OKAY33"].map(|t| PlayerData::parse(t).unwrap());
+
+        // (player, party size, tracked teammates)
+        let cases = [
+            ("me", 3, vec!["Joe#EU631"]),
+            ("random", 3, vec!["Cooper#NA123", "Joe#EU631"]),
+            // A tracked player who isn't in the party doesn't count
+            ("solo", 1, vec![]),
+            ("enemy", 1, vec![]),
+        ];
+
+        for (puuid, size, teammates) in cases {
+            let party = Party::of(&game, player(&game, puuid), &tracked);
+            assert_eq!(party.size, size, "{puuid}");
+            assert_eq!(party.tracked_teammates, teammates, "{puuid}");
+        }
+    }
+
+    #[test]
+    fn records_by_size_and_teammates() {
+        let duo = Party {
+            size: 2,
+            tracked_teammates: vec!["Joe#EU631".to_string()],
+        };
+        let random_duo = Party {
+            size: 2,
+            tracked_teammates: vec![],
+        };
+
+        let mut stats = PartyStats::default();
+        stats.record(&duo, true, Some(20), 250.0);
+        stats.record(&random_duo, false, None, 150.0);
+
+        assert_eq!(
+            stats.by_size[&2].summary(),
+            "2 games, 50% WR, 200 ACS, +20.0 RR avg"
+        );
+        assert_eq!(
+            stats.by_teammates["Joe#EU631"].summary(),
+            "1 games, 100% WR, 250 ACS, +20.0 RR avg"
+        );
+        // Without a caught MMR change there's no RR to average
+        assert_eq!(stats.by_teammates[""].summary(), "1 games, 0% WR, 150 ACS");
+        assert_eq!(SynergyTotals::default().summary(), "0 games, 0% WR, 0 ACS");
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::{fs, time::sleep};
+use tracing::{error, info};
+
+use crate::{
+    config::{Config, REGIONS},
+    guilds::SharedGuilds,
+    PlayerData,
+};
+
+/// How often the players file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A line of the players file that couldn't be parsed.
+#[derive(Debug, PartialEq)]
+pub struct LineError {
+    /// 1 based
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for LineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("line {}: {}", self.line, self.message))
+    }
+}
+
+/// Parses the players file, one `Name#Tag` per line with optional `key=value` attributes after it:
+/// ```text
+/// # Comments start with a '#', blank lines are skipped
+/// Cooper#NA123
+/// Joe#EU631 nickname="Joe B" region=eu discord=123456789012345678  # trailing comments too
+/// ```
+/// Every invalid line is reported instead of stopping at the first one.
+pub fn parse_players(contents: &str) -> Result<Vec<PlayerData>, Vec<LineError>> {
+    let mut players = Vec::<(usize, PlayerData)>::new();
+    let mut errors = vec![];
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+
+        let player = match parse_line(line) {
+            Ok(Some(p)) => p,
+            Ok(None) => continue,
+            Err(message) => {
+                errors.push(LineError {
+                    line: line_number,
+                    message,
+                });
+                continue;
+            }
+        };
+
+        if let Some((first, _)) = players.iter().find(|(_, p)| p == &player) {
+            errors.push(LineError {
+                line: line_number,
+                message: format!("{player} is already on line {first}"),
+            });
+            continue;
+        }
+
+        players.push((line_number, player));
+    }
+
+    if errors.is_empty() {
+        Ok(players.into_iter().map(|(_, p)| p).collect())
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_line(line: &str) -> Result<Option<PlayerData>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    // Names can have spaces but never a '#', and tags can't have either
+    let Some((name, rest)) = line.split_once('#') else {
+        return Err(format!(
+            "'{line}' is missing a tag, it should look like Name#Tag"
+        ));
+    };
+
+    let name = name.trim();
+    let (tag, attributes) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    let Some(mut player) = PlayerData::parse(&format!("{name}#{tag}")) else {
+        return Err(format!("'{name}#{tag}' isn't a valid Name#Tag"));
+    };
+
+    for (key, value) in parse_attributes(attributes)? {
+        match key.as_str() {
+            "nickname" if !value.is_empty() => player.nickname = Some(value),
+            "region" if REGIONS.contains(&value.to_lowercase().as_str()) => {
+                player.region = Some(value.to_lowercase())
+            }
+            "region" => {
+                return Err(format!(
+                    "unknown region '{value}', expected one of {}",
+                    REGIONS.join(", ")
+                ))
+            }
+            "discord" => match value.parse() {
+                Ok(id) => player.discord_user = Some(id),
+                Err(_) => return Err(format!("discord should be a user id, not '{value}'")),
+            },
+            _ => {
+                return Err(format!(
+                    "unknown attribute '{key}={value}', expected nickname, region or discord"
+                ))
+            }
+        }
+    }
+
+    Ok(Some(player))
+}
+
+/// Splits `key=value key2="quoted value"` pairs, stopping at a `#` comment.
+fn parse_attributes(attributes: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = vec![];
+    let mut rest = attributes.trim_start();
+
+    while !rest.is_empty() && !rest.starts_with('#') {
+        let Some((key, after_key)) = rest.split_once('=') else {
+            return Err(format!("attribute '{rest}' should look like key=value"));
+        };
+
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(format!("attribute '{key}' should look like key=value"));
+        }
+
+        let (value, after_value) = match after_key.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some(v) => v,
+                None => return Err(format!("attribute '{key}' is missing a closing quote")),
+            },
+            None => after_key
+                .split_once(char::is_whitespace)
+                .unwrap_or((after_key, "")),
+        };
+
+        pairs.push((key.to_lowercase(), value.to_string()));
+        rest = after_value.trim_start();
+    }
+
+    Ok(pairs)
+}
+
+/// Watches the players file and swaps the default guild's players whenever it changes.
+/// The poller picks up the new list on its next round, added players get their first game as a baseline without posting it.
+pub async fn watch_players_file(guilds: SharedGuilds, config: Arc<Config>) {
+    let path = &config.player_file;
+    let mut last_modified = modified(path).await;
+
+    loop {
+        sleep(WATCH_INTERVAL).await;
+
+        let modified = modified(path).await;
+        if modified == last_modified {
+            continue;
+        }
+
+        last_modified = modified;
+
+        let contents = match fs::read_to_string(path).await {
+            Ok(c) => c,
+            Err(e) => {
+                error!(file = %path.display(), "Failed to read players file -> {e}");
+                continue;
+            }
+        };
+
+        // Keep tracking the old list if the new one is broken, it's most likely mid edit
+        let players = match parse_players(&contents) {
+            Ok(p) => p,
+            Err(errors) => {
+                for e in errors {
+                    error!(file = %path.display(), "Failed to reload players file, {e}");
+                }
+                continue;
+            }
+        };
+
+        let mut guilds = guilds.write().await;
+        let default = guilds
+            .default
+            .get_or_insert_with(|| config.default_guild(vec![]));
+
+        let added = players
+            .iter()
+            .filter(|p| !default.players.contains(p))
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>();
+        let removed = default
+            .players
+            .iter()
+            .filter(|p| !players.contains(p))
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>();
+
+        info!(
+            file = %path.display(),
+            added = %added.join(", "),
+            removed = %removed.join(", "),
+            "Reloaded players file"
+        );
+
+        // Always swap so changed attributes (nickname, region...) apply to existing players too
+        default.players = players;
+    }
+}
+
+async fn modified(path: &Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path).await.and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, tag: &str) -> PlayerData {
+        PlayerData::parse(&format!("{name}#{tag}")).unwrap()
+    }
+
+    #[test]
+    fn skips_blank_lines_and_comments() {
+        let players = parse_players("# friends\n\nCooper#NA123\n   \nJoe#EU631\n\n").unwrap();
+
+        assert_eq!(
+            players,
+            vec![player("Cooper", "NA123"), player("Joe", "EU631")]
+        );
+    }
+
+    #[test]
+    fn handles_windows_line_endings() {
+        let players = parse_players("Cooper#NA123\r\nJoe#EU631\r\n").unwrap();
+
+        assert_eq!(
+            players,
+            vec![player("Cooper", "NA123"), player("Joe", "EU631")]
+        );
+    }
+
+    #[test]
+    fn allows_spaces_in_names() {
+        let players = parse_players("Big Chungus#NA1").unwrap();
+
+        assert_eq!(players[0].name, "Big Chungus");
+        assert_eq!(players[0].tag, "NA1");
+    }
+
+    #[test]
+    fn parses_attributes_and_trailing_comments() {
+        let players = parse_players(
+            "Joe#EU631 nickname=\"Joe B\" region=EU discord=123456789012345678 # the goat",
+        )
+        .unwrap();
+
+        assert_eq!(players[0].nickname.as_deref(), Some("Joe B"));
+        assert_eq!(players[0].region.as_deref(), Some("eu"));
+        assert_eq!(players[0].discord_user, Some(123456789012345678));
+    }
+
+    #[test]
+    fn reports_every_invalid_line() {
+        let errors = parse_players(
+            "Cooper#NA123\nno tag here\nJoe#EU631 region=mars\nMom#OKAY33 discord=mom\nCooper#NA123\nA#B#C",
+        )
+        .unwrap_err();
+
+        let lines = errors.iter().map(|e| e.line).collect::<Vec<usize>>();
+        assert_eq!(lines, vec![2, 3, 4, 5, 6]);
+        assert!(errors[1].message.contains("unknown region 'mars'"));
+        assert!(errors[3].message.contains("already on line 1"));
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_attributes() {
+        assert!(parse_players("Joe#EU631 color=blue").is_err());
+        assert!(parse_players("Joe#EU631 nickname").is_err());
+        assert!(parse_players("Joe#EU631 nickname=\"Joe").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tokio::time::Instant;
+use tracing::{debug, error, field, info, instrument, Span};
+
+use crate::{
+    api::Api,
+    config::Config,
+    events::{Event, EventBus},
+    guilds::SharedGuilds,
+    health::HEALTH,
+    metrics::METRICS,
+    shutdown::Shutdown,
+    store::SharedStore,
+    MmrDatum, PlayerData,
+};
+
+pub const NAME: &str = "Poller";
+
+/// Idle players wait about this fraction of the time since they last played between polls
+const IDLE_FRACTION: u32 = 10;
+
+/// What the poller remembers about a player between polls.
+#[derive(Default)]
+struct PlayerState {
+    last_game_id: Option<String>,
+    /// `date_raw` of the MMR change last sent with a match, so it's only sent with one
+    last_match_mmr: Option<i64>,
+    mmr: Option<MmrDatum>,
+    /// Unix seconds when their latest known match ended or MMR changed
+    last_played: Option<i64>,
+    /// Due right away when not set
+    next_poll: Option<Instant>,
+}
+
+impl PlayerState {
+    fn is_due(&self, now: Instant) -> bool {
+        self.next_poll.is_none_or(|at| at <= now)
+    }
+
+    fn played(&mut self, at: i64) {
+        self.last_played = self.last_played.max(Some(at));
+    }
+}
+
+/// Polls every tracked player for new matches and MMR changes, and tells the reporters about them.
+pub async fn poller_thread(
+    guilds: SharedGuilds,
+    events: EventBus,
+    store: SharedStore,
+    api: Api,
+    config: Arc<Config>,
+    mut shutdown: Shutdown,
+) {
+    let mut states = HashMap::<PlayerData, PlayerState>::new();
+
+    loop {
+        // Snapshot so guild changes don't block on a whole round of polling
+        let guilds = guilds.read().await.clone();
+        let game_players = guilds.players_where(|g| !g.game_outputs().is_empty());
+        let mmr_players = guilds.players_where(|g| !g.mmr_outputs().is_empty());
+
+        METRICS.set_players("game", &game_players);
+        METRICS.set_players("mmr", &mmr_players);
+
+        let mut players = game_players.clone();
+        players.extend(
+            mmr_players
+                .iter()
+                .filter(|p| !game_players.contains(p))
+                .cloned(),
+        );
+        states.retain(|p, _| players.contains(p));
+
+        let now = Instant::now();
+        // Recordings already only have the polls that were made, so replay all of them
+        let due = players
+            .iter()
+            .filter(|p| api.is_replaying() || states.get(*p).is_none_or(|s| s.is_due(now)))
+            .collect::<Vec<&PlayerData>>();
+
+        // No one to poll is still a healthy poller, but players who just aren't due yet say nothing about HenrikDev
+        if players.is_empty() {
+            HEALTH.polled(NAME);
+        }
+
+        for player in due {
+            // Only stop between players so a match is never half processed
+            if shutdown.is_triggered() {
+                break;
+            }
+
+            let state = states.entry(player.clone()).or_default();
+            poll_player(
+                player,
+                state,
+                game_players.contains(player),
+                mmr_players.contains(player),
+                &events,
+                &store,
+                &api,
+            )
+            .await;
+
+            let wait = interval(state.last_played.map(|at| api.now() - at), &config);
+            debug!(%player, secs = wait.as_secs(), "Next poll scheduled");
+            state.next_poll = Some(Instant::now() + wait);
+        }
+
+        METRICS.polls.with_label_values(&["poller"]).inc();
+        events.emit(Event::PollFinished { at: api.now() });
+
+        // Woken at least every poll_interval so new players don't wait on someone who's idle
+        let wait = states.values().filter_map(|s| s.next_poll).min().map_or(
+            config.poll_interval(),
+            |at| {
+                at.saturating_duration_since(Instant::now())
+                    .min(config.poll_interval())
+            },
+        );
+
+        if shutdown.is_triggered() || api.wait(&mut shutdown, wait).await {
+            break;
+        }
+    }
+
+    info!("Poller stopped.");
+}
+
+#[instrument(skip_all, fields(player = %player, match_id = field::Empty))]
+async fn poll_player(
+    player: &PlayerData,
+    state: &mut PlayerState,
+    matches: bool,
+    mmr: bool,
+    events: &EventBus,
+    store: &SharedStore,
+    api: &Api,
+) {
+    let mut mmr_polled = false;
+
+    if matches {
+        mmr_polled = poll_matches(player, state, events, store, api).await;
+    }
+
+    if mmr && !mmr_polled {
+        match api.lookup_player_mmr(player).await {
+            Ok(current) => {
+                METRICS.poll_succeeded("mmr", player);
+                HEALTH.polled(NAME);
+                update_mmr(player, state, current, events);
+            }
+            Err(e) => api_error(player, "mmr_history", e, events),
+        }
+    }
+}
+
+/// Checks for a new match, returns whether the MMR was looked up along with it.
+async fn poll_matches(
+    player: &PlayerData,
+    state: &mut PlayerState,
+    events: &EventBus,
+    store: &SharedStore,
+    api: &Api,
+) -> bool {
+    let game = match api.lookup_player_matches(player).await {
+        Ok(game) => game,
+        Err(e) => {
+            api_error(player, "matches", e, events);
+            return false;
+        }
+    };
+
+    let match_id = game.metadata.match_id.clone();
+    state.played(game.metadata.game_start + game.metadata.game_length / 1000);
+    Span::current().record("match_id", match_id.as_str());
+
+    let Some(found) = game
+        .players
+        .all_players
+        .iter()
+        .find(|p| player.is(&p.name, &p.tag))
+    else {
+        error!("Failed to find player in match players!");
+        return false;
+    };
+
+    METRICS.poll_succeeded("game", player);
+    HEALTH.polled(NAME);
+
+    let name = format!("{}#{}", found.name, found.tag);
+    let previous_name = store
+        .lock()
+        .await
+        .players
+        .insert(found.puuid.clone(), name.clone());
+    if let Some(previous_name) = previous_name.filter(|n| *n != name) {
+        info!(from = %previous_name, to = %name, "Player was renamed");
+        events.emit(Event::PlayerRenamed {
+            puuid: found.puuid.clone(),
+            from: previous_name,
+            to: name,
+        });
+    }
+
+    match state.last_game_id.replace(match_id.clone()) {
+        None => {
+            info!("No game stored, so no need to send match message");
+            return false;
+        }
+        Some(last) if last == match_id => {
+            debug!("Last stored game is same as newest");
+            return false;
+        }
+        Some(_) => {}
+    }
+
+    // What was fetched before could be from before this match
+    api.forget_mmr(player);
+    let mmr = match api.lookup_player_mmr(player).await {
+        Ok(current) => {
+            METRICS.poll_succeeded("mmr", player);
+            update_mmr(player, state, current.clone(), events);
+
+            Some(current).filter(|m| state.last_match_mmr.replace(m.date_raw) != Some(m.date_raw))
+        }
+        Err(e) => {
+            api_error(player, "mmr_history", e, events);
+            None
+        }
+    };
+
+    events.emit(Event::NewMatch {
+        player: player.clone(),
+        game: Arc::new(game),
+        mmr,
+    });
+
+    true
+}
+
+fn update_mmr(player: &PlayerData, state: &mut PlayerState, current: MmrDatum, events: &EventBus) {
+    state.played(current.date_raw);
+    let previous = state.mmr.replace(current.clone());
+
+    if previous.as_ref().map(|m| m.elo).unwrap_or_default() == current.elo {
+        return;
+    }
+
+    info!(elo = current.elo, "Detected MMR change");
+
+    if let Some(previous) = &previous {
+        if previous.current_tier_patched != current.current_tier_patched {
+            events.emit(Event::RankChanged {
+                player: player.clone(),
+                from: previous.current_tier_patched.clone(),
+                to: current.current_tier_patched.clone(),
+            });
+        }
+    }
+
+    events.emit(Event::MmrChanged {
+        player: player.clone(),
+        previous,
+        current,
+    });
+}
+
+fn api_error(player: &PlayerData, endpoint: &'static str, e: anyhow::Error, events: &EventBus) {
+    error!(endpoint, "Failed to poll HenrikDev -> {e:#}");
+    events.emit(Event::ApiError {
+        player: player.clone(),
+        endpoint,
+        error: format!("{e:#}"),
+    });
+}
+
+/// How long to wait before polling a player again, given how many seconds ago they last played.
+///
+/// Someone still in their session is polled every `min_poll_interval`, after that it backs off from `poll_interval`
+/// the longer they've been idle. Players who haven't been seen playing yet are polled every `poll_interval`.
+fn interval(idle: Option<i64>, config: &Config) -> Duration {
+    let Some(idle) = idle else {
+        return config.poll_interval();
+    };
+
+    let idle = Duration::from_secs(idle.max(0) as u64);
+    if idle < config.session_gap() {
+        return config.min_poll_interval();
+    }
+
+    (idle / IDLE_FRACTION).clamp(config.poll_interval(), config.max_poll_interval())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::config::Args;
+
+    fn config() -> Config {
+        Config::from_args(Args::parse_from([
+            "valorant_tracker",
+            "--dry-run",
+            "--poll-interval",
+            "60",
+            "--max-poll-interval",
+            "900",
+            "--session-gap",
+            "30",
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn polls_players_in_a_session_faster() {
+        let config = config();
+
+        assert_eq!(interval(Some(0), &config), Duration::from_secs(30));
+        assert_eq!(interval(Some(29 * 60), &config), Duration::from_secs(30));
+        // A clock a little behind HenrikDev's
+        assert_eq!(interval(Some(-5), &config), Duration::from_secs(30));
+        // Not seen playing yet, so there's no session to keep up with
+        assert_eq!(interval(None, &config), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn backs_off_idle_players_up_to_the_max() {
+        let config = config();
+
+        assert_eq!(
+            interval(Some(60 * 60), &config),
+            Duration::from_secs(6 * 60)
+        );
+        assert_eq!(
+            interval(Some(2 * 60 * 60), &config),
+            Duration::from_secs(12 * 60)
+        );
+        assert_eq!(
+            interval(Some(30 * 24 * 60 * 60), &config),
+            Duration::from_secs(900)
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// A player's best ever game in each category.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct PersonalRecords {
+    pub acs: Option<Record>,
+    pub kills: Option<Record>,
+    pub headshot_percentage: Option<Record>,
+    pub adr: Option<Record>,
+    pub rr_gain: Option<Record>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Record {
+    pub value: f64,
+    pub match_id: String,
+    pub map: String,
+    pub agent: String,
+    /// Unix seconds
+    pub date: i64,
+}
+
+/// The categories a player has records in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    Acs,
+    Kills,
+    HeadshotPercentage,
+    Adr,
+    RrGain,
+}
+
+impl RecordKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Acs => "Highest ACS",
+            Self::Kills => "Most Kills",
+            Self::HeadshotPercentage => "Best HS%",
+            Self::Adr => "Highest ADR",
+            Self::RrGain => "Biggest RR Gain",
+        }
+    }
+
+    pub fn format(self, value: f64) -> String {
+        match self {
+            Self::HeadshotPercentage => format!("{value:.0}%"),
+            Self::RrGain => format!("{value:+}"),
+            _ => format!("{value:.0}"),
+        }
+    }
+
+    /// Whether a game's value can be a record at all, losing RR is never a gain.
+    fn counts(self, value: f64) -> bool {
+        value.is_finite() && (self != Self::RrGain || value > 0.0)
+    }
+}
+
+/// One game's values for each record, `None` if it can't be measured (no shots hit, MMR change not caught).
+pub struct RecordCandidates {
+    pub acs: f64,
+    pub kills: f64,
+    pub headshot_percentage: Option<f64>,
+    pub adr: f64,
+    pub rr_gain: Option<f64>,
+}
+
+impl PersonalRecords {
+    fn entries_mut(&mut self) -> [(RecordKind, &mut Option<Record>); 5] {
+        [
+            (RecordKind::Acs, &mut self.acs),
+            (RecordKind::Kills, &mut self.kills),
+            (
+                RecordKind::HeadshotPercentage,
+                &mut self.headshot_percentage,
+            ),
+            (RecordKind::Adr, &mut self.adr),
+            (RecordKind::RrGain, &mut self.rr_gain),
+        ]
+    }
+
+    pub fn entries(&self) -> Vec<(RecordKind, &Record)> {
+        [
+            (RecordKind::Acs, &self.acs),
+            (RecordKind::Kills, &self.kills),
+            (RecordKind::HeadshotPercentage, &self.headshot_percentage),
+            (RecordKind::Adr, &self.adr),
+            (RecordKind::RrGain, &self.rr_gain),
+        ]
+        .into_iter()
+        .filter_map(|(kind, record)| Some((kind, record.as_ref()?)))
+        .filter(|(kind, record)| kind.counts(record.value))
+        .collect()
+    }
+
+    /// Stores every value that beats the current record, returning the records broken.
+    /// Setting a category's first record doesn't count as breaking it.
+    pub fn update(
+        &mut self,
+        candidates: RecordCandidates,
+        match_id: &str,
+        map: &str,
+        agent: &str,
+        date: i64,
+    ) -> Vec<RecordKind> {
+        let values = [
+            Some(candidates.acs),
+            Some(candidates.kills),
+            candidates.headshot_percentage,
+            Some(candidates.adr),
+            candidates.rr_gain,
+        ];
+
+        let mut broken = vec![];
+
+        for ((kind, record), value) in self.entries_mut().into_iter().zip(values) {
+            // Older versions stored RR losses as gains
+            if record.as_ref().is_some_and(|r| !kind.counts(r.value)) {
+                *record = None;
+            }
+
+            let Some(value) = value.filter(|v| kind.counts(*v)) else {
+                continue;
+            };
+
+            let had_record = record.is_some();
+            if record.as_ref().is_some_and(|r| r.value >= value) {
+                continue;
+            }
+
+            *record = Some(Record {
+                value,
+                match_id: match_id.to_string(),
+                map: map.to_string(),
+                agent: agent.to_string(),
+                date,
+            });
+
+            if had_record {
+                broken.push(kind);
+            }
+        }
+
+        broken
+    }
+}
+
+pub fn match_url(match_id: &str) -> String {
+    format!("https://tracker.gg/valorant/match/{match_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(
+        acs: f64,
+        headshot_percentage: Option<f64>,
+        rr_gain: Option<f64>,
+    ) -> RecordCandidates {
+        RecordCandidates {
+            acs,
+            kills: 20.0,
+            headshot_percentage,
+            adr: 150.0,
+            rr_gain,
+        }
+    }
+
+    #[test]
+    fn first_values_are_stored_without_counting_as_broken() {
+        let mut records = PersonalRecords::default();
+
+        let broken = records.update(
+            candidates(250.0, Some(30.0), Some(18.0)),
+            "a",
+            "Ascent",
+            "Sage",
+            1,
+        );
+
+        assert!(broken.is_empty());
+        assert_eq!(records.acs.as_ref().unwrap().value, 250.0);
+        assert_eq!(records.rr_gain.as_ref().unwrap().value, 18.0);
+    }
+
+    #[test]
+    fn only_strictly_better_values_break_records() {
+        let mut records = PersonalRecords::default();
+        records.update(
+            candidates(250.0, Some(30.0), Some(18.0)),
+            "a",
+            "Ascent",
+            "Sage",
+            1,
+        );
+
+        // (acs, hs%, rr, broken)
+        let cases = [
+            (250.0, Some(30.0), Some(18.0), vec![]),
+            (
+                249.0,
+                Some(31.0),
+                Some(17.0),
+                vec![RecordKind::HeadshotPercentage],
+            ),
+            (
+                300.0,
+                None,
+                Some(25.0),
+                vec![RecordKind::Acs, RecordKind::RrGain],
+            ),
+        ];
+
+        for (i, (acs, hs, rr, broken)) in cases.into_iter().enumerate() {
+            let id = i.to_string();
+            assert_eq!(
+                records.update(candidates(acs, hs, rr), &id, "Bind", "Jett", 2),
+                broken
+            );
+        }
+
+        // Ties keep the match that set the record first
+        assert_eq!(records.kills.as_ref().unwrap().match_id, "a");
+        assert_eq!(records.acs.as_ref().unwrap().match_id, "2");
+    }
+
+    #[test]
+    fn unmeasurable_values_and_rr_losses_are_never_records() {
+        let mut records = PersonalRecords::default();
+
+        records.update(
+            candidates(f64::NAN, Some(f64::NAN), Some(-17.0)),
+            "a",
+            "Ascent",
+            "Sage",
+            1,
+        );
+        assert!(records.acs.is_none());
+        assert!(records.headshot_percentage.is_none());
+        assert!(records.rr_gain.is_none());
+
+        records.update(
+            candidates(200.0, Some(20.0), Some(0.0)),
+            "b",
+            "Ascent",
+            "Sage",
+            2,
+        );
+        assert!(records.rr_gain.is_none());
+
+        let broken = records.update(
+            candidates(f64::INFINITY, None, Some(12.0)),
+            "c",
+            "Ascent",
+            "Sage",
+            3,
+        );
+        assert!(broken.is_empty());
+        assert_eq!(records.acs.as_ref().unwrap().value, 200.0);
+        assert_eq!(records.rr_gain.as_ref().unwrap().value, 12.0);
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{anyhow, Context, Result};
+use tokio::sync::watch;
+
+use crate::PlayerData;
+
+/// Saves every raw HenrikDev response to a directory, for `--replay-dir` to play back later.
+///
+/// Files are named `{received_at}-{seq}-{tracker}-{endpoint}-{Name#Tag}.json` and hold the body untouched,
+/// so they can be edited by hand or used as test fixtures.
+pub struct Recorder {
+    dir: PathBuf,
+    /// Keeps responses received in the same second in order
+    seq: AtomicU64,
+}
+
+impl Recorder {
+    pub fn new(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("couldn't create record directory {}", dir.display()))?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            seq: AtomicU64::new(0),
+        })
+    }
+
+    pub async fn record(
+        &self,
+        tracker: &str,
+        endpoint: &str,
+        player: &PlayerData,
+        received_at: i64,
+        body: &[u8],
+    ) -> Result<()> {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(format!(
+            "{received_at}-{seq}-{tracker}-{endpoint}-{player}.json"
+        ));
+
+        tokio::fs::write(&path, body)
+            .await
+            .with_context(|| format!("couldn't write {}", path.display()))
+    }
+}
+
+/// A recorded response, and when it was received in unix seconds.
+pub struct Recorded {
+    pub received_at: i64,
+    pub body: Vec<u8>,
+}
+
+/// Plays back responses saved by a `Recorder`, in the order each tracker got them.
+pub struct Replay {
+    /// Keyed by tracker, endpoint and `Name#Tag`
+    responses: Mutex<HashMap<(String, String, String), VecDeque<Recorded>>>,
+    finished: watch::Sender<bool>,
+}
+
+impl Replay {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut files = vec![];
+
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("couldn't read replay directory {}", dir.display()))?;
+
+        for entry in entries {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some(name) = name.strip_suffix(".json") else {
+                continue;
+            };
+
+            // Split from the left since names can have dashes in them
+            let parts = name.splitn(5, '-').collect::<Vec<&str>>();
+            let [received_at, seq, tracker, endpoint, player] = parts[..] else {
+                return Err(anyhow!(
+                    "{} isn't named like a recorded response",
+                    path.display()
+                ));
+            };
+
+            let received_at = received_at
+                .parse::<i64>()
+                .with_context(|| format!("{} doesn't start with a timestamp", path.display()))?;
+            let seq = seq
+                .parse::<u64>()
+                .with_context(|| format!("{} has no sequence number", path.display()))?;
+            let body = std::fs::read(&path)
+                .with_context(|| format!("couldn't read {}", path.display()))?;
+
+            files.push((
+                (received_at, seq),
+                (
+                    tracker.to_string(),
+                    endpoint.to_string(),
+                    player.to_string(),
+                ),
+                Recorded { received_at, body },
+            ));
+        }
+
+        files.sort_by_key(|(order, ..)| *order);
+
+        let mut responses = HashMap::<_, VecDeque<Recorded>>::new();
+        for (_, key, recorded) in files {
+            responses.entry(key).or_default().push_back(recorded);
+        }
+
+        Ok(Self {
+            finished: watch::channel(responses.is_empty()).0,
+            responses: Mutex::new(responses),
+        })
+    }
+
+    /// The next response `tracker` got from `endpoint` for `player`.
+    pub fn next(&self, tracker: &str, endpoint: &str, player: &PlayerData) -> Result<Recorded> {
+        let mut responses = self.responses.lock().unwrap();
+
+        let key = (
+            tracker.to_string(),
+            endpoint.to_string(),
+            player.to_string(),
+        );
+        let recorded = responses
+            .get_mut(&key)
+            .and_then(|r| r.pop_front())
+            .ok_or_else(|| anyhow!("no more recorded {endpoint} responses for {player}"))?;
+
+        if responses.values().all(|r| r.is_empty()) {
+            self.finished.send_replace(true);
+        }
+
+        Ok(recorded)
+    }
+
+    /// Whether `tracker` has been through all of its responses.
+    pub fn is_done(&self, tracker: &str) -> bool {
+        self.responses
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|((t, ..), r)| t != tracker || r.is_empty())
+    }
+
+    /// Waits until every tracker has been through all of its responses.
+    pub async fn finished(&self) {
+        let mut finished = self.finished.subscribe();
+
+        while !*finished.borrow() {
+            if finished.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use serde_derive::Serialize;
+
+use crate::PlayerData;
+
+/// Who a report is about.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReportPlayer {
+    /// `Name#Tag`
+    pub tag: String,
+    pub display_name: String,
+    pub discord_user: Option<u64>,
+}
+
+impl ReportPlayer {
+    /// Mention of the player's Discord user, empty if they don't have one.
+    pub fn mention(&self) -> String {
+        self.discord_user
+            .map(|id| format!("<@{id}>"))
+            .unwrap_or_default()
+    }
+}
+
+impl From<&PlayerData> for ReportPlayer {
+    fn from(player: &PlayerData) -> Self {
+        Self {
+            tag: player.to_string(),
+            display_name: player.display_name().to_string(),
+            discord_user: player.discord_user,
+        }
+    }
+}
+
+/// The presentable part of a report, an embed on Discord.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Card {
+    pub title: String,
+    pub description: String,
+    /// Green when won, red when lost and grey for a draw
+    pub won: Option<bool>,
+    /// Unix seconds
+    pub timestamp: i64,
+    pub image: Option<String>,
+    pub thumbnail: Option<String>,
+    pub fields: Vec<CardField>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CardField {
+    pub name: String,
+    pub value: String,
+}
+
+impl CardField {
+    pub fn new<A: ToString, B: ToString>(name: A, value: B) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// A tracked player's new match.
+#[derive(Serialize, Debug, Clone)]
+pub struct MatchReport {
+    pub player: ReportPlayer,
+    pub match_id: String,
+    pub map: String,
+    pub agent: String,
+    /// Differs between guilds since each has its own thresholds
+    pub streak_announcement: Option<String>,
+    pub card: Card,
+}
+
+/// Recap of a player's finished play session.
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionReport {
+    pub player: ReportPlayer,
+    pub games: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub draws: i64,
+    pub net_rr: i64,
+    pub played_ms: i64,
+    pub card: Card,
+}
+
+/// Everyone's current rank in a guild, highest first.
+#[derive(Serialize, Debug, Clone)]
+pub struct LeaderboardReport {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub player: ReportPlayer,
+    pub rank: String,
+    pub ranking_in_tier: i64,
+    pub elo: i64,
+}
+
+impl LeaderboardReport {
+    /// One line per player, the way the leaderboard message has always looked.
+    pub fn render(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} -> `{} @ {} MMR`",
+                    e.player.display_name, e.rank, e.ranking_in_tier
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
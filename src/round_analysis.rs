@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::hendrix_matches_response::{Kill, MatchDatum};
+
+/// How much a player contributed to their rounds beyond raw score.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct RoundContribution {
+    pub rounds: i64,
+    /// Rounds with a kill, assist, survival or a traded death
+    pub kast_rounds: i64,
+    /// Kills on an enemy who had just killed a teammate
+    pub trade_kills: i64,
+    /// Deaths that a teammate avenged within the window
+    pub traded_deaths: i64,
+}
+
+impl RoundContribution {
+    pub fn add(&mut self, other: &RoundContribution) {
+        self.rounds += other.rounds;
+        self.kast_rounds += other.kast_rounds;
+        self.trade_kills += other.trade_kills;
+        self.traded_deaths += other.traded_deaths;
+    }
+
+    pub fn kast(&self) -> f64 {
+        self.kast_rounds as f64 / self.rounds.max(1) as f64 * 100_f64
+    }
+}
+
+pub fn analyze(game: &MatchDatum, puuid: &str, trade_window: Duration) -> RoundContribution {
+    let window = trade_window.as_millis() as i64;
+    let mut contribution = RoundContribution {
+        rounds: game.rounds.len() as i64,
+        ..Default::default()
+    };
+
+    for round in 0..contribution.rounds {
+        let kills = game
+            .kills
+            .iter()
+            .filter(|k| k.round == Some(round) && k.killer_team != k.victim_team)
+            .collect::<Vec<&Kill>>();
+
+        let got_kill = kills.iter().any(|k| k.killer_puuid == puuid);
+        let got_assist = kills
+            .iter()
+            .any(|k| k.assistants.iter().any(|a| a.assistant_puuid == puuid));
+        let death = kills.iter().find(|k| k.victim_puuid == puuid);
+
+        let traded = death.is_some_and(|death| {
+            kills.iter().any(|k| {
+                k.victim_puuid == death.killer_puuid
+                    && k.killer_team == death.victim_team
+                    && is_within(death, k, window)
+            })
+        });
+
+        if got_kill || got_assist || death.is_none() || traded {
+            contribution.kast_rounds += 1;
+        }
+
+        if traded {
+            contribution.traded_deaths += 1;
+        }
+
+        contribution.trade_kills += kills
+            .iter()
+            .filter(|k| k.killer_puuid == puuid)
+            .filter(|k| {
+                kills.iter().any(|teammate_death| {
+                    teammate_death.killer_puuid == k.victim_puuid
+                        && teammate_death.victim_team == k.killer_team
+                        && teammate_death.victim_puuid != puuid
+                        && is_within(teammate_death, k, window)
+                })
+            })
+            .count() as i64;
+    }
+
+    contribution
+}
+
+/// Whether `revenge` happened after `death` but no later than the window.
+fn is_within(death: &Kill, revenge: &Kill, window: i64) -> bool {
+    let delay = revenge.kill_time_in_round - death.kill_time_in_round;
+    (0..=window).contains(&delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_match::TestMatch;
+
+    const WINDOW: Duration = Duration::from_secs(5);
+    const ZERO: (&str, &str) = ("zero", "Red");
+    const MATE: (&str, &str) = ("mate", "Red");
+    const ENEMY: (&str, &str) = ("enemy", "Blue");
+    const OTHER_ENEMY: (&str, &str) = ("other", "Blue");
+
+    #[test]
+    fn counts_kast_and_trades_within_the_window() {
+        let game = [ZERO, MATE, ENEMY, OTHER_ENEMY]
+            .iter()
+            .fold(TestMatch::new().rounds(5), |m, (puuid, team)| {
+                m.player(puuid, team, [0; 4])
+            })
+            // Avenged right at the edge of the window
+            .kill(0, 10_000, ENEMY, ZERO, &[])
+            .kill(0, 15_000, MATE, ENEMY, &[])
+            // A millisecond too late
+            .kill(1, 10_000, ENEMY, ZERO, &[])
+            .kill(1, 15_001, MATE, ENEMY, &[])
+            // Zero trades their teammate instantly
+            .kill(2, 20_000, OTHER_ENEMY, MATE, &[])
+            .kill(2, 20_000, ZERO, OTHER_ENEMY, &[])
+            // An assist counts even when dying after it
+            .kill(3, 5_000, MATE, ENEMY, &["zero"])
+            .kill(3, 40_000, OTHER_ENEMY, ZERO, &[])
+            // Round 4 nobody dies, surviving counts
+            .build();
+
+        // (puuid, kast rounds, trade kills, traded deaths)
+        let expected = [("zero", 4, 1, 1), ("mate", 5, 1, 1)];
+
+        for (puuid, kast_rounds, trade_kills, traded_deaths) in expected {
+            let c = analyze(&game, puuid, WINDOW);
+            assert_eq!(c.rounds, 5, "{puuid}");
+            assert_eq!(
+                (c.kast_rounds, c.trade_kills, c.traded_deaths),
+                (kast_rounds, trade_kills, traded_deaths),
+                "{puuid}"
+            );
+        }
+    }
+
+    #[test]
+    fn zero_round_match_has_no_kast() {
+        let game = TestMatch::new().player("zero", "Red", [0; 4]).build();
+
+        let contribution = analyze(&game, "zero", WINDOW);
+        assert_eq!(contribution.rounds, 0);
+        assert_eq!(contribution.kast(), 0.0);
+    }
+}
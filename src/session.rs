@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+/// A run of games where each one started within the session gap of the previous one ending.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Session {
+    /// Unix seconds
+    pub started: i64,
+    /// Unix seconds
+    pub ended: i64,
+    pub games: i64,
+    pub wins: i64,
+    pub losses: i64,
+    #[serde(default)]
+    pub draws: i64,
+    pub net_rr: i64,
+    pub played_ms: i64,
+    pub best: Option<SessionGame>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionGame {
+    pub map: String,
+    pub agent: String,
+    pub acs: f64,
+    pub kills: i64,
+    pub deaths: i64,
+    pub assists: i64,
+}
+
+impl Session {
+    pub fn new(started: i64) -> Self {
+        Self {
+            started,
+            ended: started,
+            games: 0,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            net_rr: 0,
+            played_ms: 0,
+            best: None,
+        }
+    }
+
+    /// `game_start` is in unix seconds and `game_length` in milliseconds, same as the match metadata.
+    /// `won` is `None` for a draw.
+    pub fn add(
+        &mut self,
+        game_start: i64,
+        game_length: i64,
+        won: Option<bool>,
+        rr: Option<i64>,
+        game: SessionGame,
+    ) {
+        self.games += 1;
+        self.played_ms += game_length;
+        self.ended = self.ended.max(game_start + game_length / 1000);
+        self.net_rr += rr.unwrap_or_default();
+
+        match won {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.draws += 1,
+        }
+
+        if self.best.as_ref().is_none_or(|b| game.acs > b.acs) {
+            self.best = Some(game);
+        }
+    }
+
+    /// Whether a game starting at `game_start` (unix seconds) still belongs to this session.
+    pub fn continues_with(&self, game_start: i64, gap: Duration) -> bool {
+        game_start - self.ended <= gap.as_secs() as i64
+    }
+
+    /// A session is over once the gap has elapsed since the last game without a new one showing up.
+    pub fn is_over(&self, now: i64, gap: Duration) -> bool {
+        !self.continues_with(now, gap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAP: Duration = Duration::from_secs(30 * 60);
+
+    fn game(acs: f64) -> SessionGame {
+        SessionGame {
+            map: "Ascent".to_string(),
+            agent: "Sage".to_string(),
+            acs,
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+        }
+    }
+
+    #[test]
+    fn adds_up_games() {
+        let mut session = Session::new(1_000);
+        // (start, length in ms, won, rr, acs)
+        let games = [
+            (1_000, 1_800_000, Some(true), Some(20), 250.0),
+            (3_000, 2_400_000, None, Some(0), 310.0),
+            (6_000, 1_200_000, Some(false), Some(-15), 180.0),
+            (8_000, 600_000, Some(true), None, 90.0),
+        ];
+
+        for (start, length, won, rr, acs) in games {
+            session.add(start, length, won, rr, game(acs));
+        }
+
+        assert_eq!(session.games, 4);
+        assert_eq!((session.wins, session.losses, session.draws), (2, 1, 1));
+        assert_eq!(session.net_rr, 5);
+        assert_eq!(session.played_ms, 6_000_000);
+        assert_eq!(session.ended, 8_600);
+        assert_eq!(session.best.unwrap().acs, 310.0);
+    }
+
+    #[test]
+    fn ends_the_gap_after_the_last_game_ended() {
+        let mut session = Session::new(1_000);
+        session.add(1_000, 1_800_000, Some(true), None, game(200.0));
+        let ended = 2_800;
+
+        // (game start or now, still in the session)
+        let cases = [
+            (ended, true),
+            (ended + 30 * 60, true),
+            (ended + 30 * 60 + 1, false),
+            // HenrikDev's clock can be a little ahead
+            (ended - 60, true),
+        ];
+
+        for (at, continues) in cases {
+            assert_eq!(session.continues_with(at, GAP), continues, "at {at}");
+            assert_eq!(session.is_over(at, GAP), !continues, "at {at}");
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use tokio::{select, signal, sync::watch, time::sleep};
+use tracing::error;
+
+/// Lets the trackers know the process is stopping, so they can finish up at a safe point.
+#[derive(Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    pub fn new() -> (watch::Sender<bool>, Self) {
+        let (sender, receiver) = watch::channel(false);
+        (sender, Self(receiver))
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Waits until a shutdown is triggered.
+    pub async fn triggered(&mut self) {
+        while !*self.0.borrow() {
+            // An error means the sender is gone, which only happens when shutting down anyway
+            if self.0.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Sleeps for `duration`, waking up early if a shutdown is triggered. Returns whether it was.
+    pub async fn sleep(&mut self, duration: Duration) -> bool {
+        select! {
+            _ = sleep(duration) => false,
+            _ = self.triggered() => true,
+        }
+    }
+}
+
+/// Waits for SIGINT (Ctrl+C), or SIGTERM on unix.
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to listen for SIGTERM -> {e}");
+                let _ = signal::ctrl_c().await;
+                return;
+            }
+        };
+
+        select! {
+            _ = signal::ctrl_c() => {},
+            _ = terminate.recv() => {},
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+    }
+}
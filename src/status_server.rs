@@ -0,0 +1,64 @@
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde_json::Value;
+use tracing::{error, info};
+
+use crate::{health::HEALTH, metrics::METRICS, shutdown::Shutdown};
+
+/// Serves the local status endpoints until shutdown.
+pub async fn serve(address: SocketAddr, stale_after: Duration, mut shutdown: Shutdown) {
+    let make_service = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |r| handle(r, stale_after)))
+    });
+
+    let server = match Server::try_bind(&address) {
+        Ok(s) => s.serve(make_service),
+        Err(e) => {
+            error!(%address, "Failed to start the status server -> {e}");
+            return;
+        }
+    };
+
+    info!(%address, "Status server listening");
+
+    let server = server.with_graceful_shutdown(async move { shutdown.triggered().await });
+
+    if let Err(e) = server.await {
+        error!("Status server stopped -> {e}");
+    }
+}
+
+async fn handle(
+    request: Request<Body>,
+    stale_after: Duration,
+) -> Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(Body::from(METRICS.render())),
+        (&Method::GET, "/healthz") => check(HEALTH.healthz(stale_after)),
+        (&Method::GET, "/readyz") => check(HEALTH.readyz(stale_after)),
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("Not found")),
+    };
+
+    Ok(response.unwrap())
+}
+
+/// 200 if the check passed, 503 otherwise, so supervisors can act on the status alone.
+fn check((ok, report): (bool, Value)) -> hyper::http::Result<Response<Body>> {
+    Response::builder()
+        .status(if ok {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        })
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(report.to_string()))
+}
@@ -0,0 +1,144 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex};
+use tracing::{error, info, warn};
+
+use crate::{
+    agents::AgentPool, head_to_head::HeadToHead, party::PartyStats, records::PersonalRecords,
+    round_analysis::RoundContribution, session::Session, streak::Streak,
+};
+
+pub const STATE_FILE: &str = "./state.json";
+
+pub type SharedStore = Arc<Mutex<Store>>;
+
+/// Everything the bot remembers between restarts, keyed by player puuid.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Store {
+    /// Latest known `Name#Tag` of every tracked puuid
+    #[serde(default)]
+    pub players: HashMap<String, String>,
+    #[serde(default)]
+    pub head_to_head: HashMap<String, HeadToHead>,
+    #[serde(default)]
+    pub party: HashMap<String, PartyStats>,
+    #[serde(default)]
+    pub agents: HashMap<String, AgentPool>,
+    #[serde(default)]
+    pub streaks: HashMap<String, Streak>,
+    #[serde(default)]
+    pub records: HashMap<String, PersonalRecords>,
+    #[serde(default)]
+    pub round_contributions: HashMap<String, RoundContribution>,
+    /// Sessions that haven't been recapped yet
+    #[serde(default)]
+    pub sessions: HashMap<String, Session>,
+    /// MMR leaderboard message of each webhook, since webhooks can't look up their latest message
+    #[serde(default)]
+    pub leaderboard_messages: HashMap<u64, u64>,
+    /// Set for dry runs, so trying things out doesn't change the real state
+    #[serde(skip)]
+    pub read_only: bool,
+}
+
+impl Store {
+    pub async fn load() -> Self {
+        load_json(STATE_FILE).await.unwrap_or_else(|| {
+            info!("No state file found, starting with an empty one.");
+            Self::default()
+        })
+    }
+
+    /// Resolves a `Name#Tag` (case insensitive) to the puuid we last saw it on.
+    pub fn find_puuid(&self, name: &str) -> Option<&String> {
+        self.players
+            .iter()
+            .find(|(_, n)| n.eq_ignore_ascii_case(name.trim()))
+            .map(|(puuid, _)| puuid)
+    }
+
+    pub async fn save(&self) {
+        if self.read_only {
+            return;
+        }
+
+        let json = match serde_json::to_string(self) {
+            Ok(j) => j,
+            Err(e) => {
+                error!("Failed to serialize state -> {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = write_atomically(STATE_FILE, json.as_bytes()).await {
+            error!(file = STATE_FILE, "Failed to write state -> {e}");
+        }
+    }
+}
+
+/// Reads a JSON file, `None` if it's missing or corrupt.
+/// A corrupt file is moved aside instead of being overwritten, so it can still be looked at.
+pub async fn load_json<T: DeserializeOwned>(path: &str) -> Option<T> {
+    let contents = fs::read_to_string(path).await.ok()?;
+
+    match serde_json::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let aside = format!("{path}.corrupt");
+            error!(
+                file = path,
+                "File is corrupt, moving it to {aside} and starting over -> {e}"
+            );
+
+            if let Err(e) = fs::rename(path, &aside).await {
+                warn!(file = path, "Failed to move corrupt file aside -> {e}");
+            }
+            None
+        }
+    }
+}
+
+/// Writes to a temporary file next to `path` first, so a crash halfway through never leaves a truncated file.
+pub async fn write_atomically(path: &str, contents: &[u8]) -> std::io::Result<()> {
+    let temp = format!("{path}.tmp");
+
+    let mut file = fs::File::create(&temp).await?;
+    file.write_all(contents).await?;
+    file.sync_all().await?;
+    drop(file);
+
+    fs::rename(&temp, path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn corrupt_files_are_moved_aside() {
+        let dir =
+            std::env::temp_dir().join(format!("valorant_tracker_store_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let path = path.to_str().unwrap();
+
+        assert!(load_json::<Store>(path).await.is_none());
+
+        // What a kill halfway through a plain write used to leave behind
+        std::fs::write(path, r#"{"players":{"abc":"Zero#SH"#).unwrap();
+        assert!(load_json::<Store>(path).await.is_none());
+        assert!(!std::path::Path::new(path).exists());
+        assert!(std::path::Path::new(&format!("{path}.corrupt")).exists());
+
+        write_atomically(path, br#"{"players":{"abc":"Zero#SHOT"}}"#)
+            .await
+            .unwrap();
+        let store = load_json::<Store>(path).await.unwrap();
+        assert_eq!(store.players["abc"], "Zero#SHOT");
+        assert!(!std::path::Path::new(&format!("{path}.tmp")).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
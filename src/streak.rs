@@ -0,0 +1,113 @@
+use std::fmt::{Display, Formatter};
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Consecutive wins or losses, a draw breaks either.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct Streak {
+    pub won: bool,
+    pub count: i64,
+}
+
+/// How long a streak has to get before it's announced, 0 disables the announcement.
+#[derive(Debug, Clone, Copy)]
+pub struct StreakThresholds {
+    pub win: i64,
+    pub loss: i64,
+}
+
+impl Streak {
+    /// `won` is `None` for a draw.
+    pub fn record(&mut self, won: Option<bool>) {
+        match won {
+            Some(won) if self.count > 0 && self.won == won => self.count += 1,
+            Some(won) => *self = Self { won, count: 1 },
+            None => *self = Self::default(),
+        }
+    }
+
+    /// The message to post if this streak just hit its threshold (or a multiple of it).
+    pub fn announcement(&self, player: &str, thresholds: StreakThresholds) -> Option<String> {
+        let threshold = if self.won {
+            thresholds.win
+        } else {
+            thresholds.loss
+        };
+
+        if threshold <= 0 || self.count == 0 || self.count % threshold != 0 {
+            return None;
+        }
+
+        Some(if self.won {
+            format!("🔥 **{player}** is on a {}-game win streak!", self.count)
+        } else {
+            format!(
+                "🧊 **{player}** lost {} in a row, maybe take a break.",
+                self.count
+            )
+        })
+    }
+}
+
+impl Display for Streak {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.count {
+            0 => f.write_str("-"),
+            n if self.won => f.write_fmt(format_args!("W{n}")),
+            n => f.write_fmt(format_args!("L{n}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: StreakThresholds = StreakThresholds { win: 3, loss: 2 };
+
+    #[test]
+    fn counts_and_announces_at_every_multiple() {
+        let mut streak = Streak::default();
+        let (w, l, d) = (Some(true), Some(false), None);
+
+        // (result, shown after it, announced)
+        let games = [
+            (w, "W1", false),
+            (w, "W2", false),
+            (w, "W3", true),
+            (w, "W4", false),
+            (w, "W5", false),
+            (w, "W6", true),
+            (l, "L1", false),
+            (l, "L2", true),
+            // A draw in the middle of a streak ends it
+            (d, "-", false),
+            (l, "L1", false),
+            (l, "L2", true),
+            (l, "L3", false),
+            (l, "L4", true),
+            (w, "W1", false),
+        ];
+
+        for (i, (won, shown, announced)) in games.into_iter().enumerate() {
+            streak.record(won);
+            assert_eq!(streak.to_string(), shown, "game {i}");
+            assert_eq!(
+                streak.announcement("Zero", THRESHOLDS).is_some(),
+                announced,
+                "game {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn zero_threshold_never_announces() {
+        let mut streak = Streak::default();
+        let disabled = StreakThresholds { win: 0, loss: 0 };
+
+        for _ in 0..10 {
+            streak.record(Some(true));
+            assert!(streak.announcement("Zero", disabled).is_none());
+        }
+    }
+}
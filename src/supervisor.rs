@@ -0,0 +1,89 @@
+use std::{
+    any::Any,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use serenity::{http::Http, model::id::ChannelId};
+use tokio::task;
+use tracing::error;
+
+use crate::{health::HEALTH, shutdown::Shutdown};
+
+const MIN_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+/// A task that ran this long before crashing starts over from the minimum backoff
+const STABLE_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// Runs the task made by `make`, restarting it with exponential backoff whenever it panics or stops before a shutdown.
+/// Crashes are logged and, if there is one, reported to the admin channel.
+pub async fn supervise<F, Fut>(
+    name: &'static str,
+    http: &Http,
+    admin_channel: Option<u64>,
+    mut shutdown: Shutdown,
+    make: F,
+) where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut backoff = MIN_BACKOFF;
+
+    loop {
+        let started = Instant::now();
+        HEALTH.set_alive(name, true);
+        let result = task::spawn(make()).await;
+        HEALTH.set_alive(name, false);
+
+        if shutdown.is_triggered() {
+            return;
+        }
+
+        let reason = match result {
+            Ok(()) => "stopped unexpectedly".to_string(),
+            Err(e) if e.is_panic() => format!("panicked -> {}", panic_message(e.into_panic())),
+            Err(e) => format!("was cancelled -> {e}"),
+        };
+
+        if started.elapsed() > STABLE_AFTER {
+            backoff = MIN_BACKOFF;
+        }
+
+        error!(
+            task = name,
+            backoff_secs = backoff.as_secs(),
+            "Task {reason}, restarting"
+        );
+
+        if let Some(channel) = admin_channel {
+            let report = ChannelId(channel)
+                .send_message(http, |m| {
+                    m.content(format!(
+                        "⚠️ **{name}** {reason}, restarting in {}s.",
+                        backoff.as_secs()
+                    ))
+                })
+                .await;
+
+            if let Err(e) = report {
+                error!("Failed to report crash to the admin channel -> {e}");
+            }
+        }
+
+        if shutdown.sleep(backoff).await {
+            return;
+        }
+
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
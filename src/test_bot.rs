@@ -0,0 +1,210 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+use clap::Parser;
+use serde_json::Value;
+use serenity::http::Http;
+use tokio::{
+    sync::{watch, Mutex, RwLock},
+    task::{self, JoinHandle},
+    time::{sleep, timeout},
+};
+
+use crate::{
+    api::Api,
+    config::{Args, Config},
+    events::{Event, EventBus},
+    game_log::game_log_thread,
+    guilds::{Guilds, SharedGuilds},
+    leaderboard::leaderboard_thread,
+    notifier::NotifierContext,
+    poller::poller_thread,
+    shutdown::Shutdown,
+    store::{SharedStore, Store},
+    PlayerData,
+};
+
+/// The poller and both reporters, with each feed written to its own JSON lines file.
+pub struct Bot {
+    pub api: Api,
+    pub store: SharedStore,
+    dir: PathBuf,
+    guilds: SharedGuilds,
+    notifiers: NotifierContext,
+    events: EventBus,
+    shutdown: watch::Sender<bool>,
+    poller: JoinHandle<()>,
+    reporters: Vec<JoinHandle<()>>,
+}
+
+impl Bot {
+    /// `config` is added to the top of the config file, before the outputs.
+    pub async fn start(name: &str, config: &str, players: &[&str]) -> Self {
+        Self::start_with_store(name, config, players, Store::default()).await
+    }
+
+    /// Starts with what a previous run left in the store, which is never saved.
+    pub async fn start_with_store(
+        name: &str,
+        config: &str,
+        players: &[&str],
+        store: Store,
+    ) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("valorant_tracker_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config_file = dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            format!(
+                "{config}\npoll_interval = 5\n\n\
+                 [[outputs]]\nfeed = \"game\"\nkind = \"json_lines\"\npath = {:?}\n\n\
+                 [[outputs]]\nfeed = \"mmr\"\nkind = \"json_lines\"\npath = {:?}\n",
+                dir.join("game.jsonl"),
+                dir.join("mmr.jsonl"),
+            ),
+        )
+        .unwrap();
+
+        let config = Arc::new(
+            Config::from_args(Args::parse_from([
+                "valorant_tracker",
+                "--config",
+                config_file.to_str().unwrap(),
+            ]))
+            .unwrap(),
+        );
+        let api = Api::new(&config).unwrap().for_tracker("poller");
+
+        let players = players
+            .iter()
+            .map(|p| PlayerData::parse(p).unwrap())
+            .collect();
+        let guilds = Arc::new(RwLock::new(Guilds {
+            default: Some(config.default_guild(players)),
+            ..Guilds::default()
+        }));
+        let store = Arc::new(Mutex::new(Store {
+            read_only: true,
+            ..store
+        }));
+        let notifiers = NotifierContext {
+            http: Arc::new(Http::new("")),
+            client: reqwest::Client::new(),
+            store: store.clone(),
+            dry_run: false,
+        };
+
+        let events = EventBus::new();
+        let reporters = vec![
+            task::spawn(game_log_thread(
+                guilds.clone(),
+                events.subscription().receiver(),
+                notifiers.clone(),
+                store.clone(),
+                config.clone(),
+            )),
+            task::spawn(leaderboard_thread(
+                guilds.clone(),
+                events.subscription().receiver(),
+                HashMap::new(),
+                notifiers.clone(),
+            )),
+        ];
+
+        let (shutdown_sender, shutdown) = Shutdown::new();
+        let poller = task::spawn(poller_thread(
+            guilds.clone(),
+            events.clone(),
+            store.clone(),
+            api.clone(),
+            config,
+            shutdown,
+        ));
+
+        Self {
+            api,
+            store,
+            dir,
+            guilds,
+            notifiers,
+            events,
+            shutdown: shutdown_sender,
+            poller,
+            reporters,
+        }
+    }
+
+    pub fn reports(&self, feed: &str) -> Vec<Value> {
+        std::fs::read_to_string(self.dir.join(format!("{feed}.jsonl")))
+            .unwrap_or_default()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    /// Waits for `feed` to have at least `count` reports, a few minutes of polling at most.
+    pub async fn wait_for(&self, feed: &str, count: usize) -> Vec<Value> {
+        timeout(Duration::from_secs(300), async {
+            loop {
+                let reports = self.reports(feed);
+                if reports.len() >= count {
+                    return reports;
+                }
+                sleep(Duration::from_secs(1)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("never got {count} {feed} reports"))
+    }
+
+    /// Sends an event to the reporters as if the poller found it.
+    pub fn emit(&self, event: Event) {
+        self.events.emit(event);
+    }
+
+    /// Kills the leaderboard and starts a new one, like the supervisor does when it crashes.
+    pub async fn restart_leaderboard(&mut self) {
+        let leaderboard = &mut self.reporters[1];
+        leaderboard.abort();
+        let _ = (&mut *leaderboard).await;
+
+        let subscription = self.events.subscription();
+        *leaderboard = task::spawn(leaderboard_thread(
+            self.guilds.clone(),
+            subscription.receiver(),
+            subscription.mmrs(),
+            self.notifiers.clone(),
+        ));
+    }
+
+    /// Waits for a report on `feed` that `is_expected`, returning every report up to it.
+    pub async fn wait_until(&self, feed: &str, is_expected: impl Fn(&Value) -> bool) -> Vec<Value> {
+        timeout(Duration::from_secs(300), async {
+            loop {
+                let mut reports = self.reports(feed);
+                if let Some(i) = reports.iter().position(&is_expected) {
+                    reports.truncate(i + 1);
+                    return reports;
+                }
+                sleep(Duration::from_secs(1)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("never got the expected {feed} report"))
+    }
+
+    /// Shuts down like the real bot, returning every game and MMR report.
+    pub async fn stop(mut self) -> (Vec<Value>, Vec<Value>) {
+        self.shutdown.send(true).unwrap();
+        (&mut self.poller).await.unwrap();
+        self.events.emit(Event::Stopped);
+        for reporter in &mut self.reporters {
+            reporter.await.unwrap();
+        }
+
+        let reports = (self.reports("game"), self.reports("mmr"));
+        std::fs::remove_dir_all(&self.dir).unwrap();
+        reports
+    }
+}
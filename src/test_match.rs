@@ -0,0 +1,129 @@
+use serde_json::{json, Value};
+
+use crate::MatchDatum;
+
+const TEMPLATE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/fixtures/replay/zero_shots/1700003200-2-poller-matches-Zero#SHOT.json"
+));
+
+/// Builds a match from one of the fixtures, with its players, rounds and kills replaced.
+pub struct TestMatch {
+    game: Value,
+    player: Value,
+    round: Value,
+    kill: Value,
+}
+
+impl TestMatch {
+    pub fn new() -> Self {
+        let mut response = serde_json::from_str::<Value>(TEMPLATE).unwrap();
+        let mut game = response["data"][0].take();
+
+        let player = game["players"]["all_players"][0].take();
+        let round = game["rounds"][0].take();
+        let kill = game["kills"][0].take();
+        game["players"]["all_players"] = json!([]);
+        game["rounds"] = json!([]);
+        game["kills"] = json!([]);
+
+        Self {
+            game,
+            player,
+            round,
+            kill,
+        }
+    }
+
+    /// `team` is Red or Blue, `stats` are score, kills, deaths and assists.
+    pub fn player(mut self, puuid: &str, team: &str, stats: [i64; 4]) -> Self {
+        let mut player = self.player.clone();
+        player["puuid"] = json!(puuid);
+        player["team"] = json!(team);
+        let [score, kills, deaths, assists] = stats;
+        player["stats"]["score"] = json!(score);
+        player["stats"]["kills"] = json!(kills);
+        player["stats"]["deaths"] = json!(deaths);
+        player["stats"]["assists"] = json!(assists);
+
+        self.game["players"]["all_players"]
+            .as_array_mut()
+            .unwrap()
+            .push(player);
+        self
+    }
+
+    /// Renames the player with `puuid`, they're all the fixture's player otherwise.
+    pub fn named(mut self, puuid: &str, name: &str, tag: &str) -> Self {
+        let player = self.find(puuid);
+        player["name"] = json!(name);
+        player["tag"] = json!(tag);
+        self
+    }
+
+    /// Puts the players with these puuids in one party.
+    pub fn party(mut self, party_id: &str, puuids: &[&str]) -> Self {
+        for puuid in puuids {
+            self.find(puuid)["party_id"] = json!(party_id);
+        }
+        self
+    }
+
+    fn find(&mut self, puuid: &str) -> &mut Value {
+        self.game["players"]["all_players"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|p| p["puuid"] == puuid)
+            .unwrap()
+    }
+
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.game["rounds"] = Value::Array(vec![self.round.clone(); rounds]);
+        self
+    }
+
+    /// Unix seconds.
+    pub fn started(mut self, at: i64) -> Self {
+        self.game["metadata"]["game_start"] = json!(at);
+        self
+    }
+
+    /// Rounds won by each team, the one with more won the match.
+    pub fn score(mut self, red: i64, blue: i64) -> Self {
+        for (team, won, lost) in [("red", red, blue), ("blue", blue, red)] {
+            self.game["teams"][team] =
+                json!({ "has_won": won > lost, "rounds_won": won, "rounds_lost": lost });
+        }
+        self
+    }
+
+    /// `killer` and `victim` are a puuid and team, `at` is milliseconds into the round.
+    pub fn kill(
+        mut self,
+        round: i64,
+        at: i64,
+        killer: (&str, &str),
+        victim: (&str, &str),
+        assistants: &[&str],
+    ) -> Self {
+        let mut kill = self.kill.clone();
+        kill["round"] = json!(round);
+        kill["kill_time_in_round"] = json!(at);
+        kill["killer_puuid"] = json!(killer.0);
+        kill["killer_team"] = json!(killer.1);
+        kill["victim_puuid"] = json!(victim.0);
+        kill["victim_team"] = json!(victim.1);
+        kill["assistants"] = assistants
+            .iter()
+            .map(|a| json!({ "assistant_puuid": a, "assistant_display_name": a, "assistant_team": killer.1 }))
+            .collect();
+
+        self.game["kills"].as_array_mut().unwrap().push(kill);
+        self
+    }
+
+    pub fn build(self) -> MatchDatum {
+        serde_json::from_value(self.game).unwrap()
+    }
+}